@@ -0,0 +1,77 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use bevy_egui::{EguiContexts, egui};
+use imm_sim_shared::announcement::S2CAnnouncement;
+
+use crate::connect::ConnectionState;
+
+/// How long, in seconds, an announcement stays on screen.
+const ANNOUNCEMENT_DURATION_SECS: f32 = 8.0;
+
+/// The most announcements shown at once. Older ones are dropped early to make room.
+const MAX_ANNOUNCEMENTS: usize = 5;
+
+/// Shows [`S2CAnnouncement`]s from the server at the top of the screen for a short while.
+pub struct AnnouncementsPlugin;
+
+impl Plugin for AnnouncementsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RecentAnnouncements>().add_systems(
+            Update,
+            (receive_announcements, render_announcements)
+                .chain()
+                .run_if(in_state(ConnectionState::InGame)),
+        );
+    }
+}
+
+#[derive(Default, Resource)]
+struct RecentAnnouncements {
+    /// Each announcement alongside the elapsed time at which it should be hidden, oldest first.
+    shown: VecDeque<(String, f32)>,
+}
+
+fn receive_announcements(
+    time: Res<Time>,
+    mut reader: EventReader<S2CAnnouncement>,
+    mut recent: ResMut<RecentAnnouncements>,
+) {
+    let now = time.elapsed_secs();
+
+    while recent
+        .shown
+        .front()
+        .is_some_and(|(_, hide_at)| *hide_at <= now)
+    {
+        recent.shown.pop_front();
+    }
+
+    for S2CAnnouncement { message } in reader.read() {
+        if recent.shown.len() >= MAX_ANNOUNCEMENTS {
+            recent.shown.pop_front();
+        }
+
+        recent
+            .shown
+            .push_back((message.clone(), now + ANNOUNCEMENT_DURATION_SECS));
+    }
+}
+
+fn render_announcements(mut contexts: EguiContexts, recent: Res<RecentAnnouncements>) {
+    if recent.shown.is_empty() {
+        return;
+    }
+
+    egui::Area::new(egui::Id::new("announcements"))
+        .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 16.0))
+        .show(contexts.ctx_mut(), |ui| {
+            for (message, _) in recent.shown.iter() {
+                ui.label(
+                    egui::RichText::new(format!("[Server] {message}"))
+                        .strong()
+                        .color(egui::Color32::YELLOW),
+                );
+            }
+        });
+}
@@ -0,0 +1,96 @@
+use avian3d::prelude::*;
+use bevy::prelude::*;
+use bevy_egui::EguiContexts;
+use bevy_replicon::prelude::*;
+use imm_sim_shared::{
+    carry::{C2SThrow, Carried, hold_translation},
+    physics::components::collision::carried_collision_layers,
+};
+
+use crate::{
+    camera::{OwnedCamera, position_camera},
+    connect::ConnectionState,
+    player::OwnedPlayer,
+};
+
+/// The button which throws whatever the player is carrying.
+const THROW_BUTTON: MouseButton = MouseButton::Left;
+
+/// Keeps whatever the player is carrying steady in front of the camera, and asks the server to
+/// throw it when [`THROW_BUTTON`] is pressed.
+///
+/// Objects are picked up and dropped by using them, through the [`InteractionPlugin`].
+///
+/// [`InteractionPlugin`]: crate::interaction::InteractionPlugin
+pub struct CarryPlugin;
+
+impl Plugin for CarryPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                (
+                    mirror_carried_layers,
+                    hold_own_carried_object.after(position_camera),
+                )
+                    .run_if(not(server_running)),
+                send_throw,
+            )
+                .run_if(in_state(ConnectionState::InGame)),
+        );
+    }
+}
+
+/// Let carried objects pass through players, as they do on the server, such that the player's
+/// predicted movement isn't blocked by what they carry.
+fn mirror_carried_layers(
+    added: Query<Entity, Added<Carried>>,
+    mut removed: RemovedComponents<Carried>,
+    mut commands: Commands,
+) {
+    for entity in added.iter() {
+        commands.entity(entity).insert(carried_collision_layers());
+    }
+
+    for entity in removed.read() {
+        // The object may have been despawned, rather than dropped.
+        if let Some(mut cmd) = commands.get_entity(entity) {
+            cmd.remove::<CollisionLayers>();
+        }
+    }
+}
+
+/// Place the object the player carries in front of the camera, rather than where the server last
+/// said it was, such that it doesn't trail behind as the player turns.
+fn hold_own_carried_object(
+    camera: Single<&Transform, With<OwnedCamera>>,
+    player: Single<Entity, With<OwnedPlayer>>,
+    mut carried: Query<(&Carried, &mut Transform), Without<OwnedCamera>>,
+) {
+    for (carried, mut transform) in carried.iter_mut() {
+        if carried.carrier == *player {
+            transform.translation = hold_translation(camera.translation, camera.forward());
+        }
+    }
+}
+
+fn send_throw(
+    mut contexts: EguiContexts,
+    mouse: Res<ButtonInput<MouseButton>>,
+    player: Option<Single<Entity, With<OwnedPlayer>>>,
+    carried: Query<&Carried>,
+    mut writer: EventWriter<C2SThrow>,
+) {
+    // Clicking on a window, such as the inventory, shouldn't throw anything.
+    if !mouse.just_pressed(THROW_BUTTON) || contexts.ctx_mut().wants_pointer_input() {
+        return;
+    }
+
+    let Some(player) = player else {
+        return;
+    };
+
+    if carried.iter().any(|carried| carried.carrier == *player) {
+        writer.send(C2SThrow);
+    }
+}
@@ -0,0 +1,117 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use bevy_egui::{EguiContexts, egui};
+use imm_sim_shared::{
+    announcement::S2CAnnouncement,
+    chat::{C2SChatMessage, ChatSender, MAX_CHAT_MESSAGE_LEN, S2CChatMessage},
+};
+
+use crate::connect::ConnectionState;
+
+/// The most lines kept in the chat window. Older ones are dropped to make room.
+const MAX_CHAT_LINES: usize = 100;
+
+/// A chat window in the corner of the screen while in game. Pressing Enter starts typing a message,
+/// and pressing it again sends it.
+pub struct ChatPlugin;
+
+impl Plugin for ChatPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ChatLog>()
+            .add_systems(
+                Update,
+                (receive_chat_messages, render_chat_window)
+                    .chain()
+                    .run_if(in_state(ConnectionState::InGame)),
+            )
+            .add_systems(OnExit(ConnectionState::InGame), clear_chat);
+    }
+}
+
+#[derive(Default, Resource)]
+struct ChatLog {
+    /// Each line alongside whether it came from the server rather than a player, oldest first.
+    lines: VecDeque<(String, bool)>,
+    /// The message being typed.
+    draft: String,
+}
+
+impl ChatLog {
+    fn push(&mut self, line: String, from_server: bool) {
+        if self.lines.len() >= MAX_CHAT_LINES {
+            self.lines.pop_front();
+        }
+
+        self.lines.push_back((line, from_server));
+    }
+}
+
+fn receive_chat_messages(
+    mut chat_reader: EventReader<S2CChatMessage>,
+    mut announcement_reader: EventReader<S2CAnnouncement>,
+    mut log: ResMut<ChatLog>,
+) {
+    for message in chat_reader.read() {
+        log.push(message.to_string(), message.sender == ChatSender::System);
+    }
+
+    // Announcements are also shown on their own, but are kept here such that they can be re-read.
+    for S2CAnnouncement { message } in announcement_reader.read() {
+        log.push(format!("[Server] {message}"), true);
+    }
+}
+
+fn render_chat_window(
+    mut contexts: EguiContexts,
+    mut log: ResMut<ChatLog>,
+    mut writer: EventWriter<C2SChatMessage>,
+) {
+    let log = &mut *log;
+
+    egui::Window::new("Chat")
+        .anchor(egui::Align2::LEFT_BOTTOM, egui::vec2(16.0, -16.0))
+        .default_width(360.0)
+        .resizable(false)
+        .show(contexts.ctx_mut(), |ui| {
+            egui::ScrollArea::vertical()
+                .max_height(160.0)
+                .stick_to_bottom(true)
+                .auto_shrink([false, true])
+                .show(ui, |ui| {
+                    for (line, from_server) in log.lines.iter() {
+                        let text = egui::RichText::new(line);
+                        ui.label(if *from_server {
+                            text.italics().color(egui::Color32::GRAY)
+                        } else {
+                            text
+                        });
+                    }
+                });
+
+            let response = ui.add(
+                egui::TextEdit::singleline(&mut log.draft)
+                    .hint_text("Press Enter to chat")
+                    .char_limit(MAX_CHAT_MESSAGE_LEN)
+                    .desired_width(f32::INFINITY),
+            );
+
+            let enter_pressed = ui.input(|input| input.key_pressed(egui::Key::Enter));
+            if response.lost_focus() && enter_pressed {
+                let message = log.draft.trim();
+                if !message.is_empty() {
+                    writer.send(C2SChatMessage {
+                        message: message.to_owned(),
+                    });
+                }
+
+                log.draft.clear();
+            } else if enter_pressed && !response.has_focus() {
+                response.request_focus();
+            }
+        });
+}
+
+fn clear_chat(mut log: ResMut<ChatLog>) {
+    *log = ChatLog::default();
+}
@@ -1,5 +1,7 @@
 use std::{
+    fs,
     net::{Ipv4Addr, SocketAddr, UdpSocket},
+    path::{Path, PathBuf},
     str::FromStr,
     time::{Duration, SystemTime},
 };
@@ -7,16 +9,26 @@ use std::{
 use bevy::prelude::*;
 use bevy_egui::{EguiContexts, egui};
 use bevy_renet::{
-    netcode::{ClientAuthentication, NetcodeClientTransport},
+    netcode::{ClientAuthentication, ConnectToken, NetcodeClientTransport},
     renet::{ConnectionConfig, RenetClient},
 };
 use bevy_replicon::prelude::*;
 use bevy_replicon_renet::RenetChannelsExt;
+use imm_sim_server::{
+    HandshakeLimits, ServerLifecycleCmd, ServerState, auth, config::DEFAULT_MAX_CLIENTS,
+};
 use imm_sim_shared::{
-    PROTOCOL_ID_V0_1,
+    CRATE_VERSION, PROTOCOL_ID_V0_1,
+    disconnect::S2CDisconnectNotice,
     handshake::{C2SHandshakeStart, S2CHandshakeResult},
+    level::{
+        DEFAULT_LEVEL_PATH, LevelPath, LoadedLevel,
+        definition::{LevelDefinition, LevelError},
+    },
 };
 
+use crate::camera::OwnedCamera;
+
 pub struct FormConnectionPlugin;
 
 impl Plugin for FormConnectionPlugin {
@@ -31,18 +43,46 @@ impl Plugin for FormConnectionPlugin {
                 Update,
                 render_connecting_screen.run_if(
                     in_state(ConnectionState::TryingConnection)
+                        .or(in_state(ConnectionState::StartingHost))
+                        .or(in_state(ConnectionState::SendingHandshake))
                         .or(in_state(ConnectionState::AwaitingHandshakeResponse)),
                 ),
             )
             .add_systems(OnEnter(ConnectionState::TryingConnection), process_connect)
+            .add_systems(OnEnter(ConnectionState::StartingHost), process_host)
             .add_systems(
                 Update,
-                send_handshake
-                    .run_if(in_state(ConnectionState::SendingHandshake).and(client_connected)),
+                // When hosting, handshake events are handled locally by the embedded server.
+                send_handshake.run_if(
+                    in_state(ConnectionState::SendingHandshake)
+                        .and(client_connected.or(server_running)),
+                ),
+            )
+            .add_systems(
+                Update,
+                recv_host_error.run_if(
+                    in_state(ConnectionState::SendingHandshake).and(in_state(ServerState::Errored)),
+                ),
             )
             .add_systems(
                 Update,
                 recv_handshake_result.run_if(in_state(ConnectionState::AwaitingHandshakeResponse)),
+            )
+            .add_systems(
+                Update,
+                (
+                    recv_disconnect_notice,
+                    handle_disconnect.run_if(client_just_disconnected),
+                )
+                    .chain()
+                    .run_if(
+                        in_state(ConnectionState::InGame)
+                            .or(in_state(ConnectionState::AwaitingHandshakeResponse))
+                            // When hosting, there is no connection to lose while the embedded
+                            // server starts up.
+                            .or(in_state(ConnectionState::SendingHandshake)
+                                .and(resource_exists::<RenetClient>)),
+                    ),
             );
     }
 }
@@ -52,6 +92,7 @@ pub enum ConnectionState {
     #[default]
     ConnectServerMenu,
     TryingConnection,
+    StartingHost,
     SendingHandshake,
     AwaitingHandshakeResponse,
     InGame,
@@ -60,6 +101,15 @@ pub enum ConnectionState {
 #[derive(Resource)]
 pub struct ClientId(pub u64);
 
+/// The session token handed out by the last server we joined. Should we lose our connection, it is
+/// presented on rejoining the same server under the same name, to take back our avatar.
+#[derive(Resource)]
+struct ReconnectSession {
+    server_address: String,
+    display_name: String,
+    session_token: u64,
+}
+
 #[derive(Default, Resource)]
 struct ConnectServerMenuInput {
     pub error_message: Option<String>,
@@ -67,6 +117,11 @@ struct ConnectServerMenuInput {
     pub server_address: String,
     pub server_password: String,
     pub display_name: String,
+    /// Either a connect token, or the path to a file holding one, for servers in secure mode.
+    pub connect_token: String,
+    /// The level file to play, which must match the server's. Left empty, the default level is
+    /// played.
+    pub level_path: String,
 }
 
 fn render_main_menu(
@@ -76,10 +131,10 @@ fn render_main_menu(
 ) {
     egui::Window::new("Connect to Server").show(contexts.ctx_mut(), |ui| {
         if let Some(e) = input.error_message.as_ref() {
-            ui.label(format!("Error connecting: {e}"));
+            ui.label(e.as_str());
         }
 
-        ui.label("Server Address:");
+        ui.label("Server Address (or the address to listen on when hosting):");
         ui.text_edit_singleline(&mut input.server_address);
 
         ui.label("Server Password (Optional):");
@@ -90,9 +145,21 @@ fn render_main_menu(
         ui.label("Display Name:");
         ui.text_edit_singleline(&mut input.display_name);
 
-        if ui.button("Connect").clicked() {
-            next.set(ConnectionState::TryingConnection);
-        }
+        ui.label("Connect Token or Token File (Secure Servers Only):");
+        ui.text_edit_singleline(&mut input.connect_token);
+
+        ui.label("Level File (Optional):");
+        ui.text_edit_singleline(&mut input.level_path);
+
+        ui.horizontal(|ui| {
+            if ui.button("Connect").clicked() {
+                next.set(ConnectionState::TryingConnection);
+            }
+
+            if ui.button("Host").clicked() {
+                next.set(ConnectionState::StartingHost);
+            }
+        });
     });
 }
 
@@ -105,9 +172,19 @@ fn render_connecting_screen(mut contexts: EguiContexts) {
 fn process_connect(
     channels: Res<RepliconChannels>,
     mut input: ResMut<ConnectServerMenuInput>,
+    mut level_path: ResMut<LevelPath>,
     mut next: ResMut<NextState<ConnectionState>>,
     mut commands: Commands,
 ) {
+    // Anything left over from a previous attempt no longer applies.
+    input.error_message = None;
+
+    if let Err(e) = change_level(&input, &mut level_path, &mut commands) {
+        input.error_message = Some(format!("Error loading the chosen level: {e}"));
+        next.set(ConnectionState::ConnectServerMenu);
+        return;
+    }
+
     let server_channels_config = channels.get_server_configs();
     let client_channels_config = channels.get_client_configs();
 
@@ -138,13 +215,26 @@ fn process_connect(
     let current_time = SystemTime::now()
         .duration_since(SystemTime::UNIX_EPOCH)
         .expect("System time is less than Unix epoch");
-    let client_id = current_time.as_millis() as u64;
 
-    let authentication = ClientAuthentication::Unsecure {
-        protocol_id: PROTOCOL_ID_V0_1,
-        client_id,
-        server_addr,
-        user_data: None,
+    let authentication = if input.connect_token.trim().is_empty() {
+        let client_id = current_time.as_millis() as u64;
+
+        ClientAuthentication::Unsecure {
+            protocol_id: PROTOCOL_ID_V0_1,
+            client_id,
+            server_addr,
+            user_data: None,
+        }
+    } else {
+        // The token's client ID and server addresses take the place of those chosen here.
+        match read_connect_token(&input.connect_token) {
+            Ok(connect_token) => ClientAuthentication::Secure { connect_token },
+            Err(e) => {
+                input.error_message = Some(format!("Error reading the connect token: {e}"));
+                next.set(ConnectionState::ConnectServerMenu);
+                return;
+            }
+        }
     };
 
     let transport = match NetcodeClientTransport::new(current_time, authentication, socket) {
@@ -162,11 +252,107 @@ fn process_connect(
     next.set(ConnectionState::SendingHandshake);
 }
 
+/// Read a connect token from the menu input, which may either be the token itself or a path to a
+/// file holding it.
+fn read_connect_token(input: &str) -> Result<ConnectToken, auth::AuthError> {
+    let input = input.trim();
+    let path = Path::new(input);
+
+    if path.is_file() {
+        auth::parse_connect_token(&fs::read_to_string(path)?)
+    } else {
+        auth::parse_connect_token(input)
+    }
+}
+
+/// Load the level chosen in the menu in place of the current one, should they differ. The
+/// handshake checks that this is the level the server is running.
+fn change_level(
+    input: &ConnectServerMenuInput,
+    level_path: &mut LevelPath,
+    commands: &mut Commands,
+) -> Result<(), LevelError> {
+    let path = match input.level_path.trim() {
+        "" => PathBuf::from(DEFAULT_LEVEL_PATH),
+        path => PathBuf::from(path),
+    };
+
+    if path == level_path.0 {
+        return Ok(());
+    }
+
+    let level = LevelDefinition::load(&path)?;
+    info!("Loaded level `{}`.", path.display());
+
+    level_path.0 = path;
+    commands.queue(level);
+
+    Ok(())
+}
+
+/// Start the embedded server on the address given in the menu. The local player then joins it
+/// in-process, without a [`RenetClient`], as the server's own client, and the server runs the
+/// level chosen in the menu.
+fn process_host(
+    mut writer: EventWriter<ServerLifecycleCmd>,
+    mut input: ResMut<ConnectServerMenuInput>,
+    mut level_path: ResMut<LevelPath>,
+    mut next: ResMut<NextState<ConnectionState>>,
+    mut commands: Commands,
+) {
+    if let Err(e) = change_level(&input, &mut level_path, &mut commands) {
+        input.error_message = Some(format!("Error loading the chosen level: {e}"));
+        next.set(ConnectionState::ConnectServerMenu);
+        return;
+    }
+    let bind_addr = match SocketAddr::from_str(&input.server_address) {
+        Ok(addr) => addr,
+        Err(e) => {
+            input.error_message = Some(format!("Error parsing the given server address: {e}"));
+            next.set(ConnectionState::ConnectServerMenu);
+            return;
+        }
+    };
+
+    writer.send(ServerLifecycleCmd::StartServer {
+        bind_addr,
+        room_password: if input.server_password.is_empty() {
+            None
+        } else {
+            Some(input.server_password.clone())
+        },
+        private_key: None,
+        ban_file: None,
+        max_clients: DEFAULT_MAX_CLIENTS,
+        handshake_limits: HandshakeLimits::default(),
+    });
+
+    next.set(ConnectionState::SendingHandshake);
+}
+
+/// Return to the menu should the embedded server fail to start.
+fn recv_host_error(
+    mut input: ResMut<ConnectServerMenuInput>,
+    mut next: ResMut<NextState<ConnectionState>>,
+) {
+    input.error_message = Some("The server could not be started. See the log for details.".into());
+    next.set(ConnectionState::ConnectServerMenu);
+}
+
 fn send_handshake(
     mut writer: EventWriter<C2SHandshakeStart>,
     input: Res<ConnectServerMenuInput>,
+    level: Res<LoadedLevel>,
+    session: Option<Res<ReconnectSession>>,
     mut next: ResMut<NextState<ConnectionState>>,
 ) {
+    let session_token = session
+        .filter(|session| {
+            session.server_address == input.server_address
+                && session.display_name == input.display_name
+        })
+        .map(|session| session.session_token);
+
     let event = C2SHandshakeStart {
         display_name: input.display_name.clone(),
         room_password: if input.server_password.is_empty() {
@@ -174,6 +360,9 @@ fn send_handshake(
         } else {
             Some(input.server_password.clone())
         },
+        crate_version: CRATE_VERSION.to_owned(),
+        content_hash: level.0.content_hash(),
+        session_token,
     };
 
     writer.send(event);
@@ -184,12 +373,26 @@ fn recv_handshake_result(
     mut reader: EventReader<S2CHandshakeResult>,
     mut input: ResMut<ConnectServerMenuInput>,
     mut next: ResMut<NextState<ConnectionState>>,
+    mut fixed_time: ResMut<Time<Fixed>>,
     mut commands: Commands,
 ) {
     for res in reader.read() {
         match res {
-            S2CHandshakeResult::ConnectionAccepted { client_id } => {
+            S2CHandshakeResult::ConnectionAccepted {
+                client_id,
+                session_token,
+                tick_rate_hz,
+            } => {
+                // Prediction replays inputs at the server's rate, and interpolation counts its
+                // ticks, so the fixed time-step must match. When hosting, this is the server's own.
+                fixed_time.set_timestep_hz(*tick_rate_hz);
+
                 commands.insert_resource(ClientId(*client_id));
+                commands.insert_resource(ReconnectSession {
+                    server_address: input.server_address.clone(),
+                    display_name: input.display_name.clone(),
+                    session_token: *session_token,
+                });
                 next.set(ConnectionState::InGame)
             }
             S2CHandshakeResult::ConnectionRejected { reason } => {
@@ -201,3 +404,35 @@ fn recv_handshake_result(
         }
     }
 }
+
+/// Keep the reason the server gave for disconnecting us, to show once back at the menu.
+fn recv_disconnect_notice(
+    mut reader: EventReader<S2CDisconnectNotice>,
+    mut input: ResMut<ConnectServerMenuInput>,
+) {
+    for S2CDisconnectNotice { reason } in reader.read() {
+        input.error_message = Some(format!("Disconnected from the server: {reason}"));
+    }
+}
+
+/// Clear out everything from the old connection and return to the menu.
+fn handle_disconnect(
+    mut input: ResMut<ConnectServerMenuInput>,
+    mut next: ResMut<NextState<ConnectionState>>,
+    to_despawn: Query<Entity, Or<(With<Replicated>, With<OwnedCamera>)>>,
+    mut commands: Commands,
+) {
+    if input.error_message.is_none() {
+        input.error_message = Some("Lost connection to the server.".into());
+    }
+
+    for entity in to_despawn.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    commands.remove_resource::<RenetClient>();
+    commands.remove_resource::<NetcodeClientTransport>();
+    commands.remove_resource::<ClientId>();
+
+    next.set(ConnectionState::ConnectServerMenu);
+}
@@ -1,4 +1,3 @@
-use avian3d::prelude::*;
 use bevy::prelude::*;
 
 pub struct DebugEnvironmentPlugin;
@@ -10,13 +9,6 @@ impl Plugin for DebugEnvironmentPlugin {
     }
 }
 
-pub struct Block {
-    translation: Vec3,
-    extents: Vec3,
-    color: Color,
-    rotation: Quat,
-}
-
 #[derive(Component)]
 struct Ruler(usize, Dir3, Dir3);
 
@@ -72,71 +64,7 @@ impl Command for GizmoRuler {
     }
 }
 
-impl Block {
-    pub fn new(translation: Vec3, extents: Vec3, color: Color, rotation: Quat) -> Self {
-        Self {
-            translation,
-            extents,
-            color,
-            rotation,
-        }
-    }
-}
-
-impl Command for Block {
-    fn apply(self, world: &mut World) {
-        let Vec3 { x, y, z } = self.extents;
-        let mesh_handle = world
-            .resource_mut::<Assets<Mesh>>()
-            .add(Cuboid::new(x, y, z));
-        let material_handle = world
-            .resource_mut::<Assets<StandardMaterial>>()
-            .add(self.color);
-
-        world.spawn((
-            Mesh3d(mesh_handle),
-            MeshMaterial3d(material_handle),
-            Transform::from_translation(self.translation).with_rotation(self.rotation),
-            RigidBody::Static,
-            Collider::cuboid(self.extents.x, self.extents.y, self.extents.z),
-        ));
-    }
-}
-
 pub fn setup(mut commands: Commands) {
-    commands.queue(Block::new(
-        Vec3::new(2.0, 0.5, 0.0),
-        Vec3::ONE,
-        Color::srgb(1.0, 0.0, 0.0),
-        Quat::IDENTITY,
-    ));
-    commands.queue(Block::new(
-        Vec3::new(-2.0, 0.5, 0.0),
-        Vec3::ONE,
-        Color::srgb(1.0, 0.0, 0.0),
-        Quat::IDENTITY,
-    ));
-    commands.queue(Block::new(
-        Vec3::new(0.0, 0.5, 2.0),
-        Vec3::ONE,
-        Color::srgb(0.0, 1.0, 0.0),
-        Quat::IDENTITY,
-    ));
-
-    // Half height barrier
-    commands.queue(Block::new(
-        Vec3::new(2.0, 0.75, 2.0),
-        Vec3::new(1.0, 0.5, 1.0),
-        Color::srgb(0.0, 1.0, 0.0),
-        Quat::IDENTITY,
-    ));
-    commands.queue(Block::new(
-        Vec3::NEG_Y * 0.25,
-        Vec3::new(20.0, 0.5, 20.0),
-        Color::WHITE,
-        Quat::from_axis_angle(Vec3::X, 20.0_f32.to_radians()),
-    ));
-
     commands.queue(GizmoRuler::new(
         Vec3::new(0.0, 0.0, -2.0),
         10,
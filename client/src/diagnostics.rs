@@ -0,0 +1,318 @@
+use std::{
+    collections::{BTreeMap, VecDeque},
+    time::Duration,
+};
+
+use bevy::{prelude::*, time::common_conditions::on_real_timer};
+use bevy_egui::{EguiContexts, egui};
+use bevy_renet::renet::{ChannelConfig, RenetClient};
+use bevy_replicon::{client::ClientSet, prelude::*};
+use bevy_replicon_renet::RenetChannelsExt;
+use imm_sim_shared::player::messages::client_input::C2SInputEvent;
+
+use crate::{
+    connect::ConnectionState,
+    physics::{interpolation::InterpolationClock, prediction::InputHistory},
+};
+
+/// The key which shows and hides the overlay.
+const TOGGLE_KEY: KeyCode = KeyCode::F3;
+
+/// How often a new sample is taken.
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(250);
+
+/// How many samples are kept to draw the graphs with. At four samples a second, this is the last
+/// thirty seconds.
+const MAX_SAMPLES: usize = 120;
+
+/// An overlay of the connection's health, toggled with [`TOGGLE_KEY`], to put numbers to reports of
+/// rubber-banding.
+///
+/// Nothing is measured when hosting a game, as the local player has no connection of its own.
+pub struct NetworkDiagnosticsPlugin;
+
+impl Plugin for NetworkDiagnosticsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<NetworkDiagnostics>()
+            .add_systems(OnEnter(ConnectionState::InGame), reset_diagnostics)
+            .add_systems(
+                Update,
+                (
+                    toggle_overlay,
+                    count_sent_inputs,
+                    take_sample
+                        .run_if(resource_exists::<RenetClient>.and(on_real_timer(SAMPLE_INTERVAL))),
+                    render_overlay.run_if(overlay_visible),
+                )
+                    .chain()
+                    .run_if(in_state(ConnectionState::InGame)),
+            )
+            .add_systems(
+                PreUpdate,
+                count_received_bytes
+                    .after(ClientSet::ReceivePackets)
+                    .before(ClientSet::Receive)
+                    .run_if(in_state(ConnectionState::InGame).and(resource_exists::<RenetClient>)),
+            )
+            .add_systems(
+                PostUpdate,
+                count_sent_bytes
+                    .after(ClientSet::Send)
+                    .before(ClientSet::SendPackets)
+                    .run_if(in_state(ConnectionState::InGame).and(resource_exists::<RenetClient>)),
+            );
+    }
+}
+
+#[derive(Clone, Copy, Default)]
+struct Sample {
+    rtt_ms: f32,
+    /// The fraction of packets lost, from zero to one.
+    packet_loss: f32,
+    sent_kbps: f32,
+    received_kbps: f32,
+    inputs_per_sec: f32,
+    /// See [`InterpolationClock::update_age`].
+    update_age_ms: f32,
+    unacknowledged_inputs: usize,
+}
+
+/// The bandwidth used by a single replicon channel over the last sample.
+struct ChannelBandwidth {
+    channel_id: u8,
+    kbps: f32,
+}
+
+#[derive(Default, Resource)]
+struct NetworkDiagnostics {
+    visible: bool,
+    samples: VecDeque<Sample>,
+    sent_channels: Vec<ChannelBandwidth>,
+    received_channels: Vec<ChannelBandwidth>,
+    /// Inputs sent since the last sample.
+    inputs_sent: u32,
+    /// Bytes sent on each channel since the last sample.
+    sent_bytes: BTreeMap<u8, usize>,
+    /// Bytes received on each channel since the last sample.
+    received_bytes: BTreeMap<u8, usize>,
+}
+
+fn reset_diagnostics(mut diagnostics: ResMut<NetworkDiagnostics>) {
+    // Whether the overlay is shown carries over between games.
+    *diagnostics = NetworkDiagnostics {
+        visible: diagnostics.visible,
+        ..default()
+    };
+}
+
+fn overlay_visible(diagnostics: Res<NetworkDiagnostics>) -> bool {
+    diagnostics.visible
+}
+
+fn toggle_overlay(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut diagnostics: ResMut<NetworkDiagnostics>,
+) {
+    if keyboard.just_pressed(TOGGLE_KEY) {
+        diagnostics.visible = !diagnostics.visible;
+    }
+}
+
+fn count_sent_inputs(
+    mut reader: EventReader<C2SInputEvent>,
+    mut diagnostics: ResMut<NetworkDiagnostics>,
+) {
+    diagnostics.inputs_sent += reader.read().count() as u32;
+}
+
+/// Count the bytes of every message received on each channel, before replicon reads them.
+fn count_received_bytes(
+    channels: Res<RepliconChannels>,
+    mut client: ResMut<RepliconClient>,
+    mut diagnostics: ResMut<NetworkDiagnostics>,
+) {
+    for config in channels.get_server_configs() {
+        // Reading messages takes them out of the client, so each is put back once counted.
+        let messages = client.receive(config.channel_id).collect::<Vec<_>>();
+
+        let bytes = messages.iter().map(|message| message.len()).sum::<usize>();
+        *diagnostics
+            .received_bytes
+            .entry(config.channel_id)
+            .or_default() += bytes;
+
+        for message in messages {
+            client.insert_received(config.channel_id, message);
+        }
+    }
+}
+
+/// Count the bytes of every message sent on each channel, before they are handed to the transport.
+fn count_sent_bytes(
+    mut client: ResMut<RepliconClient>,
+    mut diagnostics: ResMut<NetworkDiagnostics>,
+) {
+    // As with receiving, each message is put back once counted.
+    let messages = client.drain_sent().collect::<Vec<_>>();
+
+    for (channel_id, message) in messages {
+        *diagnostics.sent_bytes.entry(channel_id).or_default() += message.len();
+        client.send(channel_id, message);
+    }
+}
+
+fn take_sample(
+    time: Res<Time<Real>>,
+    client: Res<RenetClient>,
+    channels: Res<RepliconChannels>,
+    clock: Res<InterpolationClock>,
+    history: Res<InputHistory>,
+    mut diagnostics: ResMut<NetworkDiagnostics>,
+) {
+    let network_info = client.network_info();
+
+    let sample = Sample {
+        // Renet measures round-trip time in seconds.
+        rtt_ms: (network_info.rtt * 1000.0) as f32,
+        packet_loss: network_info.packet_loss as f32,
+        sent_kbps: (network_info.bytes_sent_per_second * 8.0 / 1000.0) as f32,
+        received_kbps: (network_info.bytes_received_per_second * 8.0 / 1000.0) as f32,
+        inputs_per_sec: diagnostics.inputs_sent as f32 / SAMPLE_INTERVAL.as_secs_f32(),
+        update_age_ms: clock
+            .update_age(time.elapsed())
+            .unwrap_or_default()
+            .as_secs_f32()
+            * 1000.0,
+        unacknowledged_inputs: history.unacknowledged(),
+    };
+
+    if diagnostics.samples.len() >= MAX_SAMPLES {
+        diagnostics.samples.pop_front();
+    }
+    diagnostics.samples.push_back(sample);
+    diagnostics.inputs_sent = 0;
+
+    let sent_bytes = std::mem::take(&mut diagnostics.sent_bytes);
+    diagnostics.sent_channels = bandwidth_per_channel(channels.get_client_configs(), &sent_bytes);
+
+    let received_bytes = std::mem::take(&mut diagnostics.received_bytes);
+    diagnostics.received_channels =
+        bandwidth_per_channel(channels.get_server_configs(), &received_bytes);
+}
+
+/// The bandwidth used by each of the given channels, from the bytes counted over a sample.
+fn bandwidth_per_channel(
+    configs: Vec<ChannelConfig>,
+    bytes: &BTreeMap<u8, usize>,
+) -> Vec<ChannelBandwidth> {
+    configs
+        .into_iter()
+        .map(|config| {
+            let bytes = bytes.get(&config.channel_id).copied().unwrap_or_default();
+
+            ChannelBandwidth {
+                channel_id: config.channel_id,
+                kbps: bytes as f32 * 8.0 / 1000.0 / SAMPLE_INTERVAL.as_secs_f32(),
+            }
+        })
+        .collect()
+}
+
+fn render_overlay(mut contexts: EguiContexts, diagnostics: Res<NetworkDiagnostics>) {
+    egui::Window::new("Network")
+        .anchor(egui::Align2::RIGHT_TOP, egui::vec2(-16.0, 16.0))
+        .resizable(false)
+        .show(contexts.ctx_mut(), |ui| {
+            let Some(latest) = diagnostics.samples.back() else {
+                ui.label("No measurements yet. Nothing is measured when hosting.");
+                return;
+            };
+
+            let samples = &diagnostics.samples;
+            egui::Grid::new("network_diagnostics")
+                .num_columns(2)
+                .spacing(egui::vec2(12.0, 4.0))
+                .show(ui, |ui| {
+                    let mut row = |label: String, values: Vec<f32>| {
+                        ui.label(label);
+                        graph(ui, &values);
+                        ui.end_row();
+                    };
+
+                    row(
+                        format!("RTT: {:.0} ms", latest.rtt_ms),
+                        samples.iter().map(|s| s.rtt_ms).collect(),
+                    );
+                    row(
+                        format!("Packet loss: {:.1}%", latest.packet_loss * 100.0),
+                        samples.iter().map(|s| s.packet_loss).collect(),
+                    );
+                    row(
+                        format!("Sent: {:.1} kbps", latest.sent_kbps),
+                        samples.iter().map(|s| s.sent_kbps).collect(),
+                    );
+                    row(
+                        format!("Received: {:.1} kbps", latest.received_kbps),
+                        samples.iter().map(|s| s.received_kbps).collect(),
+                    );
+                    row(
+                        format!("Inputs sent: {:.0}/s", latest.inputs_per_sec),
+                        samples.iter().map(|s| s.inputs_per_sec).collect(),
+                    );
+                    row(
+                        format!("Last server update: {:.0} ms ago", latest.update_age_ms),
+                        samples.iter().map(|s| s.update_age_ms).collect(),
+                    );
+                    row(
+                        format!("Unacknowledged inputs: {}", latest.unacknowledged_inputs),
+                        samples
+                            .iter()
+                            .map(|s| s.unacknowledged_inputs as f32)
+                            .collect(),
+                    );
+                });
+
+            ui.separator();
+            ui.label("Sent per channel:");
+            for channel in diagnostics.sent_channels.iter() {
+                ui.label(format!(
+                    "  Channel {}: {:.1} kbps",
+                    channel.channel_id, channel.kbps
+                ));
+            }
+
+            ui.label("Received per channel:");
+            for channel in diagnostics.received_channels.iter() {
+                ui.label(format!(
+                    "  Channel {}: {:.1} kbps",
+                    channel.channel_id, channel.kbps
+                ));
+            }
+        });
+}
+
+/// Draw a small line graph of the given values, scaled such that the largest reaches the top.
+fn graph(ui: &mut egui::Ui, values: &[f32]) {
+    let (rect, _) = ui.allocate_exact_size(egui::vec2(160.0, 24.0), egui::Sense::hover());
+    ui.painter()
+        .rect_filled(rect, 2.0, ui.visuals().extreme_bg_color);
+
+    let max = values.iter().copied().fold(f32::EPSILON, f32::max);
+    let step = rect.width() / (MAX_SAMPLES - 1) as f32;
+
+    let points = values
+        .iter()
+        .enumerate()
+        .map(|(i, value)| {
+            egui::pos2(
+                rect.left() + i as f32 * step,
+                rect.bottom() - value / max * rect.height(),
+            )
+        })
+        .collect();
+
+    ui.painter().add(egui::Shape::line(
+        points,
+        egui::Stroke::new(1.0, egui::Color32::LIGHT_GREEN),
+    ));
+}
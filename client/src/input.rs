@@ -3,13 +3,12 @@ use bevy::{
     prelude::*,
     utils::{HashMap, hashbrown::hash_map::Entry},
 };
-use imm_sim_shared::player::messages::client_input::{C2SInputEvent, DigitalInput};
-
-use crate::connect::ConnectionState;
+use bevy_egui::EguiContexts;
+use imm_sim_shared::player::messages::client_input::{
+    C2SInputEvent, DigitalInput, MAX_ROTATION_PER_INPUT,
+};
 
-// TODO: Make [`Resource`]
-pub const CAMERA_SENSITIVITY_X: f32 = 1.0;
-pub const CAMERA_SENSITIVITY_Y: f32 = 1.0;
+use crate::{camera::CameraConfig, connect::ConnectionState, physics::prediction::InputHistory};
 
 pub struct InputCollectionPlugin;
 
@@ -77,17 +76,25 @@ fn gather_input(
     mut acc_keyboard: ResMut<KeyboardInputAcc>,
 
     time: Res<Time>,
+    camera_config: Res<CameraConfig>,
     keyboard_input: Res<ButtonInput<KeyCode>>,
     mouse_motion_input: Res<AccumulatedMouseMotion>,
+    mut contexts: EguiContexts,
 ) {
     let Vec2 { x, y } = mouse_motion_input.delta;
 
-    let rotation_pitch = time.delta_secs() * CAMERA_SENSITIVITY_Y * 45.0 * -y;
-    let rotation_yaw = time.delta_secs() * CAMERA_SENSITIVITY_X * 45.0 * -x;
+    let sensitivity = camera_config.sensitivity();
+    let rotation_pitch = time.delta_secs() * sensitivity.y * 45.0 * -y;
+    let rotation_yaw = time.delta_secs() * sensitivity.x * 45.0 * -x;
 
     acc_mouse.rotation_pitch += rotation_pitch;
     acc_mouse.rotation_yaw += rotation_yaw;
 
+    // Keys pressed while typing, as into the chat window, are not meant for the player's avatar.
+    if contexts.ctx_mut().wants_keyboard_input() {
+        return;
+    }
+
     for key in keyboard_input.get_just_pressed() {
         acc_keyboard.record(*key, DigitalInput::StartPress);
     }
@@ -101,8 +108,9 @@ fn gather_input(
     }
 }
 
-fn send_input(
+pub(crate) fn send_input(
     mut writer: EventWriter<C2SInputEvent>,
+    mut history: ResMut<InputHistory>,
 
     mut acc_mouse: ResMut<MouseInputAcc>,
     mut acc_keyboard: ResMut<KeyboardInputAcc>,
@@ -135,13 +143,19 @@ fn send_input(
         walk
     };
 
-    let rotation_pitch = acc_mouse.rotation_pitch;
-    let rotation_yaw = acc_mouse.rotation_yaw;
+    // The server refuses anything further, which a very fast flick of the mouse could reach.
+    let rotation_pitch = acc_mouse
+        .rotation_pitch
+        .clamp(-MAX_ROTATION_PER_INPUT, MAX_ROTATION_PER_INPUT);
+    let rotation_yaw = acc_mouse
+        .rotation_yaw
+        .clamp(-MAX_ROTATION_PER_INPUT, MAX_ROTATION_PER_INPUT);
 
     let crouch_button = acc_keyboard.get(&KeyCode::ControlLeft);
     let jump_button = acc_keyboard.get(&KeyCode::Space);
 
     let input = C2SInputEvent {
+        sequence: history.next_sequence(),
         translation_strafe,
         translation_walk,
         rotation_pitch,
@@ -151,6 +165,7 @@ fn send_input(
     };
 
     writer.send(input);
+    history.push(input);
 
     acc_keyboard.clear();
     acc_mouse.clear();
@@ -0,0 +1,116 @@
+use avian3d::prelude::*;
+use bevy::{prelude::*, render::primitives::Aabb};
+use bevy_egui::{EguiContexts, egui};
+use imm_sim_shared::interaction::{C2SInteract, Interactable, cast_interaction_ray};
+
+use crate::{camera::OwnedCamera, connect::ConnectionState};
+
+/// The key which uses whatever the player is looking at.
+const USE_KEY: KeyCode = KeyCode::KeyE;
+
+/// The furthest a ray is cast from the camera looking for an [`Interactable`]. Each one may only be
+/// used from within its own range, which should be shorter than this.
+const MAX_LOOK_DISTANCE: f32 = 10.0;
+
+/// Finds the [`Interactable`] that the player is looking at, outlines it and shows its prompt, and
+/// asks the server to use it when [`USE_KEY`] is pressed.
+pub struct InteractionPlugin;
+
+impl Plugin for InteractionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<InteractionTarget>()
+            .add_systems(
+                Update,
+                (
+                    find_interaction_target,
+                    (highlight_target, render_prompt, send_interact),
+                )
+                    .chain()
+                    .run_if(in_state(ConnectionState::InGame)),
+            )
+            .add_systems(OnExit(ConnectionState::InGame), clear_target);
+    }
+}
+
+/// The [`Interactable`] the player is looking at and is within range of, if any.
+#[derive(Default, PartialEq, Resource)]
+pub struct InteractionTarget(pub Option<Entity>);
+
+fn find_interaction_target(
+    spatial_query: SpatialQuery,
+    collider_parents: Query<&ColliderParent>,
+    camera: Single<&Transform, With<OwnedCamera>>,
+    interactables: Query<&Interactable>,
+    mut target: ResMut<InteractionTarget>,
+) {
+    let hit = cast_interaction_ray(
+        &spatial_query,
+        &collider_parents,
+        camera.translation,
+        camera.forward(),
+        MAX_LOOK_DISTANCE,
+    );
+
+    let found = hit.and_then(|(entity, distance)| {
+        let interactable = interactables.get(entity).ok()?;
+        (distance <= interactable.range).then_some(entity)
+    });
+
+    target.set_if_neq(InteractionTarget(found));
+}
+
+fn highlight_target(
+    target: Res<InteractionTarget>,
+    bounds: Query<(&GlobalTransform, &Aabb)>,
+    mut gizmos: Gizmos,
+) {
+    let Some((transform, aabb)) = target.0.and_then(|entity| bounds.get(entity).ok()) else {
+        return;
+    };
+
+    // Slightly larger than the entity itself, such that the outline isn't hidden inside its faces.
+    let outline = Transform::from_translation(aabb.center.into())
+        .with_scale(Vec3::from(aabb.half_extents) * 2.02);
+
+    gizmos.cuboid(transform.mul_transform(outline), Color::WHITE);
+}
+
+fn render_prompt(
+    mut contexts: EguiContexts,
+    target: Res<InteractionTarget>,
+    interactables: Query<&Interactable>,
+) {
+    let Some(interactable) = target.0.and_then(|entity| interactables.get(entity).ok()) else {
+        return;
+    };
+
+    egui::Area::new(egui::Id::new("interaction_prompt"))
+        .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 48.0))
+        .show(contexts.ctx_mut(), |ui| {
+            ui.label(
+                egui::RichText::new(format!("[E] {}", interactable.prompt))
+                    .strong()
+                    .color(egui::Color32::WHITE),
+            );
+        });
+}
+
+fn send_interact(
+    mut contexts: EguiContexts,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    target: Res<InteractionTarget>,
+    mut writer: EventWriter<C2SInteract>,
+) {
+    // Typing an E into the chat shouldn't open a door.
+    if !keyboard.just_pressed(USE_KEY) || contexts.ctx_mut().wants_keyboard_input() {
+        return;
+    }
+
+    if let Some(target) = target.0 {
+        writer.send(C2SInteract { target });
+    }
+}
+
+fn clear_target(mut target: ResMut<InteractionTarget>) {
+    target.0 = None;
+}
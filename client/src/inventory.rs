@@ -0,0 +1,130 @@
+use bevy::prelude::*;
+use bevy_egui::{EguiContexts, egui};
+use imm_sim_shared::{
+    inventory::{C2SInventoryCommand, INVENTORY_COLUMNS, Inventory},
+    ownership::OwnedByClient,
+};
+
+use crate::connect::{ClientId, ConnectionState};
+
+/// The key which opens and closes the inventory.
+const TOGGLE_KEY: KeyCode = KeyCode::KeyI;
+
+/// The size of each slot in the inventory grid.
+const SLOT_SIZE: egui::Vec2 = egui::vec2(88.0, 48.0);
+
+/// A window showing the player's own [`Inventory`], toggled with [`TOGGLE_KEY`].
+///
+/// Clicking a stack picks it up, and clicking another slot asks the server to move it there.
+/// Right-clicking a stack drops it into the world.
+pub struct InventoryPlugin;
+
+impl Plugin for InventoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<InventoryScreen>()
+            .add_systems(
+                Update,
+                (toggle_inventory, render_inventory.run_if(inventory_open))
+                    .chain()
+                    .run_if(in_state(ConnectionState::InGame)),
+            )
+            .add_systems(OnExit(ConnectionState::InGame), close_inventory);
+    }
+}
+
+#[derive(Default, Resource)]
+struct InventoryScreen {
+    open: bool,
+    /// The slot whose stack was picked up, to be moved to the next slot clicked.
+    selected: Option<usize>,
+}
+
+fn inventory_open(screen: Res<InventoryScreen>) -> bool {
+    screen.open
+}
+
+fn toggle_inventory(
+    mut contexts: EguiContexts,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut screen: ResMut<InventoryScreen>,
+) {
+    // Typing an I into the chat shouldn't open the inventory.
+    if keyboard.just_pressed(TOGGLE_KEY) && !contexts.ctx_mut().wants_keyboard_input() {
+        screen.open = !screen.open;
+        screen.selected = None;
+    }
+}
+
+fn render_inventory(
+    mut contexts: EguiContexts,
+    client_id: Option<Res<ClientId>>,
+    inventories: Query<(&Inventory, &OwnedByClient)>,
+    mut screen: ResMut<InventoryScreen>,
+    mut writer: EventWriter<C2SInventoryCommand>,
+) {
+    // When hosting, every player's inventory is in the world, so look for our own.
+    let Some(inventory) = client_id.and_then(|client_id| {
+        inventories
+            .iter()
+            .find(|(_, owner)| owner.client_id == client_id.0)
+            .map(|(inventory, _)| inventory)
+    }) else {
+        return;
+    };
+
+    let mut open = screen.open;
+    egui::Window::new("Inventory")
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .collapsible(false)
+        .resizable(false)
+        .open(&mut open)
+        .show(contexts.ctx_mut(), |ui| {
+            egui::Grid::new("inventory_slots")
+                .num_columns(INVENTORY_COLUMNS)
+                .spacing(egui::vec2(4.0, 4.0))
+                .show(ui, |ui| {
+                    for (slot, stack) in inventory.slots().iter().enumerate() {
+                        let text = match stack {
+                            Some(stack) if stack.count > 1 => {
+                                format!("{}\n×{}", stack.item, stack.count)
+                            }
+                            Some(stack) => stack.item.to_string(),
+                            None => String::new(),
+                        };
+
+                        let response = ui.add(
+                            egui::Button::new(text)
+                                .min_size(SLOT_SIZE)
+                                .selected(screen.selected == Some(slot)),
+                        );
+
+                        if response.clicked() {
+                            match screen.selected.take() {
+                                Some(from) if from != slot => {
+                                    writer.send(C2SInventoryCommand::Move { from, to: slot });
+                                }
+                                Some(_) => {}
+                                None if stack.is_some() => screen.selected = Some(slot),
+                                None => {}
+                            }
+                        } else if response.secondary_clicked() && stack.is_some() {
+                            writer.send(C2SInventoryCommand::Drop { slot });
+                            screen.selected = None;
+                        }
+
+                        if (slot + 1) % INVENTORY_COLUMNS == 0 {
+                            ui.end_row();
+                        }
+                    }
+                });
+        });
+
+    if !open {
+        screen.open = false;
+        screen.selected = None;
+    }
+}
+
+fn close_inventory(mut screen: ResMut<InventoryScreen>) {
+    *screen = InventoryScreen::default();
+}
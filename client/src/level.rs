@@ -0,0 +1,157 @@
+use avian3d::prelude::*;
+use bevy::{ecs::system::SystemParam, prelude::*};
+use bevy_replicon::prelude::*;
+use imm_sim_shared::{
+    level::{Door, LevelBlock, LevelLight, LevelPlugin, Pickup, Prop, definition::LightKind},
+    physics::components::{collision::pickup_collision_layers, transform::ReplicatedTransform},
+};
+
+/// Loads the level through the shared [`LevelPlugin`], then renders its blocks, lights and any props,
+/// doors or pickups replicated from the server.
+pub struct ClientLevelPlugin;
+
+impl Plugin for ClientLevelPlugin {
+    fn build(&self, app: &mut App) {
+        // The embedded server may have already added this when hosting a game.
+        if !app.is_plugin_added::<LevelPlugin>() {
+            app.add_plugins(LevelPlugin);
+        }
+
+        app.add_systems(
+            Update,
+            (
+                spawn_block_meshes,
+                spawn_level_lights,
+                spawn_prop_meshes,
+                spawn_door_meshes,
+                spawn_pickup_meshes,
+            ),
+        );
+    }
+}
+
+/// Spawn the [`Mesh3d`] and [`MeshMaterial3d`] for any [`LevelBlock`] that does not currently have
+/// one.
+fn spawn_block_meshes(
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    query: Query<(Entity, &LevelBlock), Without<Mesh3d>>,
+    mut commands: Commands,
+) {
+    for (entity, block) in query.iter() {
+        let Vec3 { x, y, z } = block.extents;
+        let mesh = meshes.add(Cuboid::new(x, y, z));
+        let material = materials.add(block.color);
+
+        commands
+            .entity(entity)
+            .insert((Mesh3d(mesh), MeshMaterial3d(material)));
+    }
+}
+
+fn spawn_level_lights(
+    query: Query<(Entity, &LevelLight), Added<LevelLight>>,
+    mut commands: Commands,
+) {
+    for (entity, light) in query.iter() {
+        let mut cmd = commands.entity(entity);
+
+        match light.kind {
+            LightKind::Point => cmd.insert(PointLight {
+                color: light.color,
+                intensity: light.intensity,
+                shadows_enabled: true,
+                ..Default::default()
+            }),
+            LightKind::Directional => cmd.insert(DirectionalLight {
+                color: light.color,
+                illuminance: light.intensity,
+                shadows_enabled: true,
+                ..Default::default()
+            }),
+        };
+    }
+}
+
+/// Gives replicated cuboids, such as props and doors, their meshes and local colliders.
+#[derive(SystemParam)]
+struct ReplicatedCuboids<'w, 's> {
+    meshes: ResMut<'w, Assets<Mesh>>,
+    materials: ResMut<'w, Assets<StandardMaterial>>,
+    replicon_server: Res<'w, RepliconServer>,
+    commands: Commands<'w, 's>,
+}
+
+impl ReplicatedCuboids<'_, '_> {
+    /// Insert the [`Mesh3d`] and [`MeshMaterial3d`] for a cuboid of the given extents and color.
+    /// Outside of hosting, it also gets a kinematic collider on the given layers, such that the
+    /// local player can bump into it, which is then moved by interpolation.
+    fn insert(
+        &mut self,
+        entity: Entity,
+        extents: Vec3,
+        color: Color,
+        transform: &ReplicatedTransform,
+        collision_layers: Option<CollisionLayers>,
+    ) {
+        let Vec3 { x, y, z } = extents;
+        let mesh = self.meshes.add(Cuboid::new(x, y, z));
+        let material = self.materials.add(color);
+
+        let mut cmd = self.commands.entity(entity);
+        cmd.insert((Mesh3d(mesh), MeshMaterial3d(material)));
+
+        // When hosting a game, this is the server's own entity and its physics are already set up.
+        if !self.replicon_server.is_running() {
+            cmd.insert((
+                Transform::from(*transform),
+                RigidBody::Kinematic,
+                Collider::cuboid(x, y, z),
+            ));
+
+            if let Some(collision_layers) = collision_layers {
+                cmd.insert(collision_layers);
+            }
+        }
+    }
+}
+
+/// Spawn the mesh, and outside of hosting the collider, for any replicated [`Prop`] that does not
+/// currently have one.
+fn spawn_prop_meshes(
+    query: Query<(Entity, &Prop, &ReplicatedTransform), Without<Mesh3d>>,
+    mut cuboids: ReplicatedCuboids,
+) {
+    for (entity, prop, transform) in query.iter() {
+        cuboids.insert(entity, prop.extents, prop.color, transform, None);
+    }
+}
+
+/// Spawn the mesh, and outside of hosting the collider, for any replicated [`Door`] that does not
+/// currently have one.
+fn spawn_door_meshes(
+    query: Query<(Entity, &Door, &ReplicatedTransform), Without<Mesh3d>>,
+    mut cuboids: ReplicatedCuboids,
+) {
+    for (entity, door, transform) in query.iter() {
+        cuboids.insert(entity, door.extents, door.color, transform, None);
+    }
+}
+
+/// Spawn the mesh, and outside of hosting the collider, for any replicated [`Pickup`] that does not
+/// currently have one. The collider is on the pickup layer, such that it can be looked at to be
+/// picked up.
+fn spawn_pickup_meshes(
+    query: Query<(Entity, &Pickup, &ReplicatedTransform), Without<Mesh3d>>,
+    mut cuboids: ReplicatedCuboids,
+) {
+    for (entity, pickup, transform) in query.iter() {
+        cuboids.insert(
+            entity,
+            pickup.extents,
+            pickup.color,
+            transform,
+            Some(pickup_collision_layers()),
+        );
+    }
+}
@@ -1,17 +1,26 @@
 use bevy::prelude::*;
-use imm_sim_shared::ProtocolPlugin;
+use imm_sim_server::ImmSimServerPlugin;
+use imm_sim_shared::{FIXED_TIMESTEP_HZ, ProtocolPlugin};
 
 use self::{
     connect::FormConnectionPlugin, input::InputCollectionPlugin, physics::ClientPhysicsPlugin,
     player::ClientPlayerPlugin,
 };
 
+pub mod announcements;
 pub mod camera;
+pub mod carry;
+pub mod chat;
 pub mod connect;
 pub mod debug_environment;
+pub mod diagnostics;
 pub mod input;
+pub mod interaction;
+pub mod inventory;
+pub mod level;
 pub mod physics;
 pub mod player;
+pub mod player_list;
 
 pub struct ImmSimClientPlugin;
 
@@ -19,6 +28,11 @@ impl Plugin for ImmSimClientPlugin {
     fn build(&self, app: &mut App) {
         // Protocol
         app.add_plugins(ProtocolPlugin);
+        // Inputs are sent on the fixed time-step, which has to match the server's for prediction.
+        // This is only the default, as the server says its tick rate on joining.
+        app.insert_resource(Time::<Fixed>::from_hz(FIXED_TIMESTEP_HZ));
+        // Embedded server, used when hosting a game from the menu.
+        app.add_plugins(ImmSimServerPlugin::alongside_client());
         // GUI to connect to a server.
         app.add_plugins(FormConnectionPlugin);
         // Collect inputs and commands
@@ -27,7 +41,24 @@ impl Plugin for ImmSimClientPlugin {
         app.add_plugins((ClientPhysicsPlugin, ClientPlayerPlugin));
         // ClientSide Camera
         app.add_plugins(camera::CameraPlugin);
-        // Simple geometry to test movement
-        app.add_plugins(debug_environment::DebugEnvironmentPlugin);
+        // Messages from the server's operator
+        app.add_plugins(announcements::AnnouncementsPlugin);
+        // Text chat between players
+        app.add_plugins(chat::ChatPlugin);
+        // Who is connected, shown while a key is held
+        app.add_plugins(player_list::PlayerListPlugin);
+        // Connection health, to diagnose rubber-banding
+        app.add_plugins(diagnostics::NetworkDiagnosticsPlugin);
+        // Using things in the world
+        app.add_plugins(interaction::InteractionPlugin);
+        // What the player carries
+        app.add_plugins(inventory::InventoryPlugin);
+        // Carrying and throwing physics objects
+        app.add_plugins(carry::CarryPlugin);
+        // Level geometry, and debug helpers to test movement
+        app.add_plugins((
+            level::ClientLevelPlugin,
+            debug_environment::DebugEnvironmentPlugin,
+        ));
     }
 }
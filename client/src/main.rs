@@ -1,9 +1,9 @@
 use avian3d::prelude::*;
 use bevy::prelude::*;
 use bevy_inspector_egui::quick::WorldInspectorPlugin;
-use bevy_replicon::RepliconPlugins;
 use bevy_replicon_renet::RepliconRenetPlugins;
 use imm_sim::ImmSimClientPlugin;
+use imm_sim_server::replicon_plugins;
 
 fn main() {
     let mut app = App::new();
@@ -11,7 +11,7 @@ fn main() {
     // Default plugins, physics-related plugins, and netcode-related plugins in that order.
     app.add_plugins(DefaultPlugins)
         .add_plugins((PhysicsPlugins::default(), PhysicsDebugPlugin::default()))
-        .add_plugins((RepliconPlugins, RepliconRenetPlugins));
+        .add_plugins((replicon_plugins(), RepliconRenetPlugins));
 
     // If running in a debug buld, include the [`WorldInspectorPlugin`].
     #[cfg(debug_assertions)]
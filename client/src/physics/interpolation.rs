@@ -0,0 +1,275 @@
+use std::{collections::VecDeque, time::Duration};
+
+use bevy::prelude::*;
+use bevy_replicon::{client::ClientSet, prelude::*};
+use imm_sim_shared::physics::components::transform::{
+    ReplicatedTransform, S2CServerTick, TransformTick,
+};
+
+use crate::{connect::ConnectionState, player::OwnedPlayer};
+
+/// The most snapshots that will be kept for any one entity.
+const MAX_SNAPSHOTS: usize = 32;
+
+/// Should the render clock drift further than this many ticks from where it ought to be, it is
+/// snapped back rather than eased.
+const CLOCK_SNAP_THRESHOLD: f64 = 10.0;
+
+/// How quickly the render clock is eased towards its target, per second.
+const CLOCK_CORRECTION_RATE: f64 = 2.0;
+
+/// Renders every replicated entity other than the [`OwnedPlayer`] slightly in the past, blending
+/// between the two server snapshots either side of that point in time.
+///
+/// When no newer snapshot has arrived yet, the entity is extrapolated along its last known motion
+/// for a short while before being held in place.
+///
+/// The server only sends an entity's transform while it moves, and once more when it comes to rest,
+/// so an entity at rest is held where it stopped until just before it next moves.
+///
+/// When hosting a game, the server's entities are used directly and nothing is interpolated.
+pub struct InterpolationPlugin;
+
+impl Plugin for InterpolationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<InterpolationConfig>()
+            .init_resource::<InterpolationClock>()
+            // A new server, or a restarted one, counts its ticks afresh.
+            .add_systems(OnEnter(ConnectionState::InGame), reset_clock)
+            .add_systems(
+                PreUpdate,
+                (
+                    buffer_snapshots,
+                    receive_server_ticks,
+                    advance_clock,
+                    interpolate_transforms,
+                )
+                    .chain()
+                    .after(ClientSet::Receive)
+                    .run_if(in_state(ConnectionState::InGame).and(not(server_running))),
+            );
+    }
+}
+
+/// Settings for how far behind the server remote entities are rendered.
+#[derive(Resource)]
+pub struct InterpolationConfig {
+    /// How many ticks behind the server's latest tick entities are rendered.
+    ///
+    /// Larger values hide more packet loss and jitter at the cost of showing older state.
+    pub delay_ticks: f32,
+
+    /// How many ticks past the newest snapshot an entity may be extrapolated before it is frozen.
+    pub max_extrapolation_ticks: f32,
+}
+
+impl Default for InterpolationConfig {
+    fn default() -> Self {
+        Self {
+            delay_ticks: 3.0,
+            max_extrapolation_ticks: 3.0,
+        }
+    }
+}
+
+/// The server tick, with a fractional part, that remote entities are currently being rendered at.
+#[derive(Default, Resource)]
+pub struct InterpolationClock {
+    render_tick: Option<f64>,
+    newest_tick: u32,
+    /// When, in real time, the server's latest tick arrived.
+    newest_tick_received_at: Option<Duration>,
+}
+
+impl InterpolationClock {
+    /// How long ago the last update from the server arrived, given the current real time, once any
+    /// has arrived.
+    pub fn update_age(&self, now: Duration) -> Option<Duration> {
+        self.newest_tick_received_at
+            .map(|received_at| now.saturating_sub(received_at))
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Snapshot {
+    tick: u32,
+    transform: ReplicatedTransform,
+}
+
+/// The recent server snapshots received for a single entity, oldest first.
+#[derive(Component, Default)]
+pub struct SnapshotBuffer {
+    snapshots: VecDeque<Snapshot>,
+}
+
+impl SnapshotBuffer {
+    fn push(&mut self, tick: u32, transform: ReplicatedTransform) {
+        // Anything older than what has already been received is of no use.
+        if self.snapshots.back().is_some_and(|last| last.tick >= tick) {
+            return;
+        }
+
+        // Nothing is sent while an entity is at rest, so rather than blending across all the time it
+        // sat still, hold it in place until the tick before it started moving again.
+        if let Some(last) = self.snapshots.back().copied() {
+            let was_at_rest = self
+                .snapshots
+                .iter()
+                .rev()
+                .nth(1)
+                .is_none_or(|previous| previous.transform == last.transform);
+
+            if was_at_rest && tick - last.tick > 1 {
+                self.push_back(Snapshot {
+                    tick: tick - 1,
+                    transform: last.transform,
+                });
+            }
+        }
+
+        self.push_back(Snapshot { tick, transform });
+    }
+
+    fn push_back(&mut self, snapshot: Snapshot) {
+        if self.snapshots.len() >= MAX_SNAPSHOTS {
+            self.snapshots.pop_front();
+        }
+
+        self.snapshots.push_back(snapshot);
+    }
+
+    /// Sample the buffer at the given tick, extrapolating up to `max_extrapolation` ticks past the
+    /// newest snapshot.
+    fn sample(&self, tick: f64, max_extrapolation: f64) -> Option<Transform> {
+        let newest = self.snapshots.back()?;
+
+        if tick >= newest.tick as f64 {
+            let Some(previous) = self.snapshots.iter().rev().nth(1) else {
+                return Some(newest.transform.into());
+            };
+
+            let ahead = (tick - newest.tick as f64).min(max_extrapolation);
+            let span = (newest.tick - previous.tick) as f64;
+            let t = 1.0 + ahead / span;
+
+            return Some(blend(&previous.transform, &newest.transform, t as f32));
+        }
+
+        let after_index = self
+            .snapshots
+            .iter()
+            .position(|snapshot| snapshot.tick as f64 > tick)?;
+
+        let Some(before_index) = after_index.checked_sub(1) else {
+            return Some(self.snapshots[after_index].transform.into());
+        };
+
+        let before = &self.snapshots[before_index];
+        let after = &self.snapshots[after_index];
+        let t = (tick - before.tick as f64) / (after.tick - before.tick) as f64;
+
+        Some(blend(&before.transform, &after.transform, t as f32))
+    }
+}
+
+/// Blend between two transforms, where a `t` above 1.0 continues along the same motion.
+fn blend(from: &ReplicatedTransform, to: &ReplicatedTransform, t: f32) -> Transform {
+    let rotation = if t <= 1.0 {
+        from.rotation.slerp(to.rotation, t)
+    } else {
+        // Continue rotating by the same delta, rather than slerping past the end.
+        let delta = to.rotation * from.rotation.inverse();
+        Quat::IDENTITY.slerp(delta, t - 1.0) * to.rotation
+    };
+
+    Transform {
+        translation: from.translation.lerp(to.translation, t),
+        rotation,
+        scale: from.scale.lerp(to.scale, t.min(1.0)),
+    }
+}
+
+fn reset_clock(mut clock: ResMut<InterpolationClock>) {
+    *clock = InterpolationClock::default();
+}
+
+fn buffer_snapshots(
+    mut query: Query<
+        (
+            Entity,
+            &ReplicatedTransform,
+            &TransformTick,
+            Option<&mut SnapshotBuffer>,
+        ),
+        (Changed<TransformTick>, Without<OwnedPlayer>),
+    >,
+    mut commands: Commands,
+) {
+    for (entity, transform, tick, buffer) in query.iter_mut() {
+        if let Some(mut buffer) = buffer {
+            buffer.push(tick.0, *transform);
+        } else {
+            let mut buffer = SnapshotBuffer::default();
+            buffer.push(tick.0, *transform);
+            commands.entity(entity).insert(buffer);
+        }
+    }
+}
+
+/// Keep track of the server's latest tick. This, rather than the ticks of the snapshots themselves,
+/// drives the render clock, as nothing else is sent while every entity is at rest.
+fn receive_server_ticks(
+    time: Res<Time<Real>>,
+    mut reader: EventReader<S2CServerTick>,
+    mut clock: ResMut<InterpolationClock>,
+) {
+    for S2CServerTick(tick) in reader.read() {
+        clock.newest_tick = clock.newest_tick.max(*tick);
+        clock.newest_tick_received_at = Some(time.elapsed());
+    }
+}
+
+/// Advance the render clock in step with real time, easing it towards sitting `delay_ticks` behind
+/// the server's latest tick to absorb jitter in when packets arrive.
+fn advance_clock(
+    time: Res<Time>,
+    fixed_time: Res<Time<Fixed>>,
+    config: Res<InterpolationConfig>,
+    mut clock: ResMut<InterpolationClock>,
+) {
+    let target = clock.newest_tick as f64 - config.delay_ticks as f64;
+
+    let render_tick = match clock.render_tick {
+        Some(render_tick) => {
+            // The fixed time-step is set to the server's tick rate on joining.
+            let ticks_per_sec = fixed_time.timestep().as_secs_f64().recip();
+            let advanced = render_tick + time.delta_secs_f64() * ticks_per_sec;
+            let drift = target - advanced;
+
+            if drift.abs() > CLOCK_SNAP_THRESHOLD {
+                target
+            } else {
+                advanced + drift * (CLOCK_CORRECTION_RATE * time.delta_secs_f64()).min(1.0)
+            }
+        }
+        None => target,
+    };
+
+    clock.render_tick = Some(render_tick);
+}
+
+fn interpolate_transforms(
+    config: Res<InterpolationConfig>,
+    clock: Res<InterpolationClock>,
+    mut query: Query<(&SnapshotBuffer, &mut Transform), Without<OwnedPlayer>>,
+) {
+    let Some(render_tick) = clock.render_tick else {
+        return;
+    };
+
+    for (buffer, mut transform) in query.iter_mut() {
+        if let Some(sampled) = buffer.sample(render_tick, config.max_extrapolation_ticks as f64) {
+            *transform = sampled;
+        }
+    }
+}
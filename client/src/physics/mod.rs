@@ -1,24 +1,24 @@
 use bevy::prelude::*;
-use bevy_replicon::client::ClientSet;
-use imm_sim_shared::physics::components::transform::ReplicatedTransform;
+use imm_sim_shared::physics::ground::GroundDetectionPlugin;
 
-use crate::connect::ConnectionState;
+use self::{interpolation::InterpolationPlugin, prediction::PredictionPlugin};
 
+pub mod interpolation;
+pub mod prediction;
+
+/// Keeps the client's view of replicated transforms in sync with the server.
+///
+/// The [`OwnedPlayer`](crate::player::OwnedPlayer) is predicted locally, while everything else is
+/// interpolated between server snapshots.
 pub struct ClientPhysicsPlugin;
 
 impl Plugin for ClientPhysicsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
-            PreUpdate,
-            mirror_transforms
-                .run_if(in_state(ConnectionState::InGame))
-                .after(ClientSet::Receive),
-        );
-    }
-}
+        // The embedded server may have already added this when hosting a game.
+        if !app.is_plugin_added::<GroundDetectionPlugin>() {
+            app.add_plugins(GroundDetectionPlugin);
+        }
 
-fn mirror_transforms(mut query: Query<(&ReplicatedTransform, &mut Transform)>) {
-    for (replica, mut transform) in query.iter_mut() {
-        *transform = (*replica).into();
+        app.add_plugins((PredictionPlugin, InterpolationPlugin));
     }
 }
@@ -0,0 +1,387 @@
+use std::collections::VecDeque;
+
+use avian3d::prelude::*;
+use bevy::prelude::*;
+use bevy_replicon::{client::ClientSet, prelude::*};
+use imm_sim_shared::{
+    physics::{
+        components::{
+            collision::{CoLayer, PLAYER_HEIGHT, player_sweep_shape},
+            movement::{Crouching, Grounded, JumpImpulse, MovementAcceleration},
+            transform::ReplicatedTransform,
+            velocity::ReplicatedLinearVelocity,
+        },
+        ground::cast_for_ground,
+    },
+    player::{
+        components::{AcknowledgedInput, LookDirection},
+        messages::client_input::C2SInputEvent,
+        movement,
+    },
+};
+
+use crate::{connect::ConnectionState, input::send_input, player::OwnedPlayer};
+
+/// Predicted positions within this distance of the server's are considered correct.
+pub const RECONCILIATION_TOLERANCE: f32 = 0.05;
+
+/// The most inputs that will be held awaiting acknowledgement. At 30 Hz, this is a little over four
+/// seconds of round-trip time.
+const MAX_INPUT_HISTORY: usize = 128;
+
+/// The most surfaces a replayed input may slide along before the replay gives up on it.
+const MAX_REPLAY_SLIDES: usize = 4;
+
+/// How far a replayed player is kept from the surfaces it runs into, such that the next cast
+/// doesn't start inside them.
+const REPLAY_SKIN_WIDTH: f32 = 0.01;
+
+/// Runs the [`OwnedPlayer`]'s movement locally as soon as an input is sent, rather than waiting on
+/// the server.
+///
+/// Every sent input is kept in the [`InputHistory`] along with the position it led to. When the
+/// server acknowledges an input, the position it reports is compared against the prediction for
+/// that same input. Should they disagree, the player is moved to the server's state and all inputs
+/// that the server has yet to process are replayed on top of it, sweeping the player through the
+/// level and checking for the ground at every step. Should the replay get stuck, the player is left
+/// at the server's state instead.
+///
+/// None of this runs when hosting a game, as the local player is then simulated by the server.
+pub struct PredictionPlugin;
+
+impl Plugin for PredictionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<InputHistory>()
+            // Each new avatar starts with no inputs acknowledged.
+            .add_systems(OnEnter(ConnectionState::InGame), reset_input_history)
+            .add_systems(
+                FixedUpdate,
+                predict_owned_player
+                    .after(send_input)
+                    .run_if(in_state(ConnectionState::InGame).and(not(server_running))),
+            )
+            .add_systems(
+                FixedPostUpdate,
+                record_predicted_translation
+                    .after(PhysicsSet::Sync)
+                    .run_if(in_state(ConnectionState::InGame).and(not(server_running))),
+            )
+            .add_systems(
+                PreUpdate,
+                reconcile_owned_player
+                    .after(ClientSet::Receive)
+                    .run_if(in_state(ConnectionState::InGame).and(not(server_running))),
+            );
+    }
+}
+
+/// Where the [`OwnedPlayer`] is looking, including every look input that the server has yet to
+/// acknowledge.
+///
+/// The replicated [`LookDirection`] lags behind by a round trip, so the camera follows this instead.
+#[derive(Component, Debug)]
+pub struct PredictedLook(pub LookDirection);
+
+struct PredictedInput {
+    input: C2SInputEvent,
+    /// Whether applying this input made the player jump.
+    jumped: bool,
+    /// Where the player ended up after the physics step following this input.
+    translation: Option<Vec3>,
+}
+
+/// Every input sent to the server which has yet to be acknowledged.
+#[derive(Default, Resource)]
+pub struct InputHistory {
+    last_sequence: u32,
+    inputs: VecDeque<PredictedInput>,
+}
+
+impl InputHistory {
+    /// Claim the sequence number for the next input to be sent.
+    pub fn next_sequence(&mut self) -> u32 {
+        self.last_sequence += 1;
+        self.last_sequence
+    }
+
+    pub fn push(&mut self, input: C2SInputEvent) {
+        if self.inputs.len() >= MAX_INPUT_HISTORY {
+            self.inputs.pop_front();
+        }
+
+        self.inputs.push_back(PredictedInput {
+            input,
+            jumped: false,
+            translation: None,
+        });
+    }
+
+    /// How many sent inputs the server has yet to acknowledge.
+    pub fn unacknowledged(&self) -> usize {
+        self.inputs.len()
+    }
+
+    /// Forget every input up to and including the given sequence number, returning the translation
+    /// that was predicted for it.
+    fn acknowledge(&mut self, sequence: u32) -> Option<Vec3> {
+        let mut predicted = None;
+
+        while let Some(front) = self.inputs.front() {
+            if front.input.sequence > sequence {
+                break;
+            }
+
+            if front.input.sequence == sequence {
+                predicted = front.translation;
+            }
+
+            self.inputs.pop_front();
+        }
+
+        predicted
+    }
+}
+
+fn reset_input_history(mut history: ResMut<InputHistory>) {
+    *history = InputHistory::default();
+}
+
+/// Apply the most recently sent input to the [`OwnedPlayer`] using the same movement code as the
+/// server.
+fn predict_owned_player(
+    time: Res<Time>,
+    mut history: ResMut<InputHistory>,
+    player: Single<
+        (
+            Entity,
+            &mut PredictedLook,
+            &mut Rotation,
+            &mut LinearVelocity,
+            &MovementAcceleration,
+            &JumpImpulse,
+            Has<Grounded>,
+        ),
+        With<OwnedPlayer>,
+    >,
+    mut commands: Commands,
+) {
+    let (entity, mut look, mut rotation, mut lin_vel, acceleration, jump_impulse, is_grounded) =
+        player.into_inner();
+
+    let Some(latest) = history.inputs.back_mut() else {
+        return;
+    };
+
+    if latest.translation.is_some() {
+        // This input has already been simulated.
+        return;
+    }
+
+    look.0
+        .rotate(latest.input.rotation_pitch, latest.input.rotation_yaw);
+    rotation.0 = look.0.body_rotation();
+
+    if movement::wants_jump(&latest.input, is_grounded) {
+        latest.jumped = true;
+        lin_vel.y += jump_impulse.0;
+        commands.entity(entity).remove::<Grounded>();
+    }
+
+    movement::accelerate(
+        &latest.input,
+        rotation.0,
+        acceleration.0,
+        time.delta_secs(),
+        &mut lin_vel.0,
+    );
+}
+
+/// Once the physics step has run, store where the latest input took the [`OwnedPlayer`].
+fn record_predicted_translation(
+    mut history: ResMut<InputHistory>,
+    player: Single<&Position, With<OwnedPlayer>>,
+) {
+    if let Some(latest) = history.inputs.back_mut() {
+        latest.translation.get_or_insert(player.0);
+    }
+}
+
+/// Compare the server's state for the last acknowledged input against what was predicted for it,
+/// rewinding and replaying the unacknowledged inputs should they differ.
+///
+/// The look direction is always rebuilt from the server's, as look inputs are deltas and one that
+/// was dropped on the way to the server would otherwise leave the two permanently out of step.
+fn reconcile_owned_player(
+    time: Res<Time<Fixed>>,
+    gravity: Res<Gravity>,
+    spatial_query: SpatialQuery,
+    mut history: ResMut<InputHistory>,
+    player: Single<
+        (
+            &ReplicatedTransform,
+            &ReplicatedLinearVelocity,
+            &LookDirection,
+            &AcknowledgedInput,
+            &MovementAcceleration,
+            &JumpImpulse,
+            Has<Crouching>,
+            &mut Position,
+            &mut PredictedLook,
+            &mut Rotation,
+            &mut LinearVelocity,
+        ),
+        (With<OwnedPlayer>, Changed<AcknowledgedInput>),
+    >,
+) {
+    let (
+        replica,
+        replica_velocity,
+        replica_look,
+        acknowledged,
+        acceleration,
+        jump_impulse,
+        is_crouching,
+        mut position,
+        mut predicted_look,
+        mut rotation,
+        mut lin_vel,
+    ) = player.into_inner();
+
+    let predicted = history.acknowledge(acknowledged.sequence);
+
+    let mut look = *replica_look;
+    for predicted in history.inputs.iter() {
+        look.rotate(predicted.input.rotation_pitch, predicted.input.rotation_yaw);
+    }
+    predicted_look.0 = look;
+    rotation.0 = look.body_rotation();
+
+    let Some(predicted) = predicted else {
+        return;
+    };
+
+    let error = predicted.distance(replica.translation);
+    if error <= RECONCILIATION_TOLERANCE {
+        return;
+    }
+
+    debug!(
+        "Mispredicted input {} by {error}. Replaying {} inputs.",
+        acknowledged.sequence,
+        history.inputs.len()
+    );
+
+    let delta_secs = time.timestep().as_secs_f32();
+    let mut translation = replica.translation;
+    let mut velocity = replica_velocity.0;
+    let mut look = *replica_look;
+    let (shape, offset) = player_sweep_shape(PLAYER_HEIGHT, is_crouching);
+    let mut is_reproduced = true;
+
+    for predicted in history.inputs.iter_mut() {
+        // Anything without a translation has not been through a physics step yet, and will be
+        // simulated from the corrected state as normal.
+        let Some(predicted_translation) = predicted.translation.as_mut() else {
+            break;
+        };
+
+        look.rotate(predicted.input.rotation_pitch, predicted.input.rotation_yaw);
+
+        let is_grounded = cast_for_ground(&spatial_query, translation, velocity).is_some();
+        predicted.jumped = movement::wants_jump(&predicted.input, is_grounded);
+
+        if predicted.jumped {
+            velocity.y += jump_impulse.0;
+        } else if !is_grounded {
+            velocity += gravity.0 * delta_secs;
+        }
+
+        movement::accelerate(
+            &predicted.input,
+            look.body_rotation(),
+            acceleration.0,
+            delta_secs,
+            &mut velocity,
+        );
+
+        let Some((swept_translation, swept_velocity)) = sweep(
+            &spatial_query,
+            &shape,
+            offset,
+            translation,
+            velocity * delta_secs,
+            velocity,
+        ) else {
+            is_reproduced = false;
+            break;
+        };
+
+        translation = swept_translation;
+        velocity = swept_velocity;
+        *predicted_translation = translation;
+    }
+
+    if !is_reproduced {
+        debug!(
+            "Could not replay the inputs after {}. Snapping to the server's state.",
+            acknowledged.sequence
+        );
+
+        translation = replica.translation;
+        velocity = replica_velocity.0;
+
+        // Leave the remaining inputs to be corrected by the next acknowledgement.
+        for predicted in history.inputs.iter_mut() {
+            if let Some(predicted_translation) = predicted.translation.as_mut() {
+                *predicted_translation = translation;
+            }
+        }
+    }
+
+    position.0 = translation;
+    lin_vel.0 = velocity;
+}
+
+/// Move a player's `shape` by `motion` through the level, sliding along whatever it runs into.
+///
+/// Returns where the player ended up, along with its velocity less whatever went into the surfaces
+/// it hit, or `None` should it still be running into something after [`MAX_REPLAY_SLIDES`].
+fn sweep(
+    spatial_query: &SpatialQuery,
+    shape: &Collider,
+    offset: Vec3,
+    mut translation: Vec3,
+    mut motion: Vec3,
+    mut velocity: Vec3,
+) -> Option<(Vec3, Vec3)> {
+    let filter = SpatialQueryFilter::from_mask(CoLayer::Environment);
+
+    for _ in 0..MAX_REPLAY_SLIDES {
+        let Ok((direction, distance)) = Dir3::new_and_length(motion) else {
+            return Some((translation, velocity));
+        };
+
+        let config = ShapeCastConfig::from_max_distance(distance + REPLAY_SKIN_WIDTH);
+        let Some(hit) = spatial_query.cast_shape(
+            shape,
+            translation + offset,
+            Quat::IDENTITY,
+            direction,
+            &config,
+            &filter,
+        ) else {
+            return Some((translation + motion, velocity));
+        };
+
+        let travelled = (hit.distance - REPLAY_SKIN_WIDTH).max(0.0);
+        translation += direction * travelled;
+
+        // Slide along the surface with whatever motion is left, as the physics step would.
+        let normal = hit.normal1;
+        motion = direction * (distance - travelled);
+        motion -= normal * motion.dot(normal).min(0.0);
+        velocity -= normal * velocity.dot(normal).min(0.0);
+    }
+
+    None
+}
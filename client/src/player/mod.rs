@@ -1,29 +1,37 @@
 use avian3d::prelude::*;
 use bevy::prelude::*;
+use bevy_replicon::prelude::*;
 use imm_sim_shared::{
     ownership::OwnedByClient,
-    physics::components::movement::{JumpImpulse, LateralDamping, MovementAcceleration, SlopeData},
+    physics::components::collision::{
+        PLAYER_HEIGHT, generate_collision_components, generate_collision_layers,
+    },
+    physics::components::movement::{Crouching, JumpImpulse, LateralDamping, SlopeData},
     physics::components::transform::ReplicatedTransform,
-    player::components::PlayerAvatarColor,
+    player::components::{LookDirection, PlayerAvatarColor},
 };
 
-use crate::camera::{CameraConfig, OwnedCamera};
 use crate::connect::{ClientId, ConnectionState};
-
-mod collision;
+use crate::{camera::OwnedCamera, physics::prediction::PredictedLook};
 
 /// At present this plugin will manage any client-side state for the [`Player`]-related entities.
 ///
 /// This includes:
 ///   1. Spawning the [`Mesh3d`] and [`MeshMaterial3d`] when a new player joins.
 ///   2. Updating the [`MeshMaterial3d`] when a [`PlayerAvatarColor`] changes.
+///   3. Shortening the [`Mesh3d`] of any player who is [`Crouching`].
 pub struct ClientPlayerPlugin;
 
 impl Plugin for ClientPlayerPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(
             Update,
-            (spawn_player_mesh, mutate_player_color).run_if(in_state(ConnectionState::InGame)),
+            (
+                spawn_player_mesh,
+                mutate_player_color,
+                resize_crouching_mesh,
+            )
+                .run_if(in_state(ConnectionState::InGame)),
         );
     }
 }
@@ -38,12 +46,14 @@ fn spawn_player_mesh(
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
     this_client: Res<ClientId>,
+    replicon_server: Res<RepliconServer>,
 
     query: Query<
         (
             &PlayerAvatarColor,
             &ReplicatedTransform,
             &OwnedByClient,
+            &LookDirection,
             Entity,
         ),
         Without<Mesh3d>,
@@ -51,40 +61,53 @@ fn spawn_player_mesh(
 
     mut commands: Commands,
 ) {
-    for (color, transform, owned_by, entity) in query.iter() {
+    for (color, transform, owned_by, look, entity) in query.iter() {
         let mesh = meshes.add(Capsule3d::new(0.3, 2.0));
         let material = materials.add(StandardMaterial::from_color(color.0));
-
-        let (shape_caster, player_top, player_bottom) =
-            collision::generate_collision_components(1.0);
-
-        let collision_layers = collision::generate_collision_layers();
+        let is_owned = this_client.0 == owned_by.client_id;
 
         let mut cmd = commands.entity(entity);
         cmd.insert((
             // Debug mesh and material
             Mesh3d(mesh),
             MeshMaterial3d(material),
-            RigidBody::Dynamic,
-            Transform::from_translation(transform.translation).rotate(transform.rotation),
-            LockedAxes::ROTATION_LOCKED,
-            JumpImpulse::default(),
-            MovementAcceleration::default(),
-            LateralDamping::default(),
-            SlopeData::default(),
-            shape_caster,
-            collision_layers,
-        ))
-        .with_children(|parent| {
-            parent.spawn(player_top);
-            parent.spawn(player_bottom);
-        });
-
-        if this_client.0 == owned_by.client_id {
+        ));
+
+        if is_owned {
             cmd.insert(OwnedPlayer);
+        }
+
+        // When hosting a game, this is the server's own entity and its physics are already set up.
+        if !replicon_server.is_running() {
+            let (shape_caster, player_top, player_bottom) =
+                generate_collision_components(PLAYER_HEIGHT);
+
+            let collision_layers = generate_collision_layers();
+
+            cmd.insert((
+                Transform::from_translation(transform.translation).rotate(transform.rotation),
+                LockedAxes::ROTATION_LOCKED,
+                JumpImpulse::default(),
+                LateralDamping::default(),
+                SlopeData::default(),
+                shape_caster,
+                collision_layers,
+            ))
+            .with_children(|parent| {
+                parent.spawn(player_top);
+                parent.spawn(player_bottom);
+            });
 
+            // Only the owned player is simulated locally. Everyone else is moved by interpolation.
+            if is_owned {
+                cmd.insert((RigidBody::Dynamic, PredictedLook(*look)));
+            } else {
+                cmd.insert(RigidBody::Kinematic);
+            }
+        }
+
+        if is_owned {
             commands.spawn((Camera3d::default(), Transform::default(), OwnedCamera));
-            commands.insert_resource(CameraConfig::default());
         }
     }
 }
@@ -101,3 +124,21 @@ fn mutate_player_color(
         material.0 = new_material;
     }
 }
+
+/// Swap the [`Mesh3d`] of a player between the standing and crouching capsules whenever the
+/// replicated [`Crouching`] component is added or removed, or a crouching player first gets a mesh.
+fn resize_crouching_mesh(
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut removed: RemovedComponents<Crouching>,
+    mut query: Query<(&mut Mesh3d, Has<Crouching>)>,
+    added: Query<Entity, (With<Crouching>, Or<(Added<Crouching>, Added<Mesh3d>)>)>,
+) {
+    for entity in removed.read().chain(added.iter()) {
+        let Ok((mut mesh, is_crouching)) = query.get_mut(entity) else {
+            continue;
+        };
+
+        let length = if is_crouching { 1.0 } else { 2.0 };
+        mesh.0 = meshes.add(Capsule3d::new(0.3, length));
+    }
+}
@@ -0,0 +1,81 @@
+use bevy::prelude::*;
+use bevy_egui::{EguiContexts, egui};
+use imm_sim_shared::{
+    ownership::OwnedByClient,
+    player::components::{
+        Disconnected, Player, PlayerAvatarColor, PlayerDisplayName, RoundTripTime,
+    },
+};
+
+use crate::connect::{ClientId, ConnectionState};
+
+/// The key held to show the player list.
+const PLAYER_LIST_KEY: KeyCode = KeyCode::Tab;
+
+/// Lists every player in the game, alongside their avatar color and round-trip time, for as long
+/// as [`PLAYER_LIST_KEY`] is held.
+pub struct PlayerListPlugin;
+
+impl Plugin for PlayerListPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            render_player_list.run_if(in_state(ConnectionState::InGame).and(player_list_key_held)),
+        );
+    }
+}
+
+fn player_list_key_held(keyboard: Res<ButtonInput<KeyCode>>) -> bool {
+    keyboard.pressed(PLAYER_LIST_KEY)
+}
+
+fn render_player_list(
+    mut contexts: EguiContexts,
+    client_id: Option<Res<ClientId>>,
+    players: Query<
+        (
+            &PlayerDisplayName,
+            &PlayerAvatarColor,
+            &RoundTripTime,
+            &OwnedByClient,
+            Has<Disconnected>,
+        ),
+        With<Player>,
+    >,
+) {
+    let mut players = players.iter().collect::<Vec<_>>();
+    players.sort_by_cached_key(|(display_name, ..)| display_name.0.to_lowercase());
+
+    egui::Window::new(format!("Players ({})", players.len()))
+        .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 64.0))
+        .collapsible(false)
+        .resizable(false)
+        .show(contexts.ctx_mut(), |ui| {
+            egui::Grid::new("player_list")
+                .num_columns(3)
+                .spacing(egui::vec2(12.0, 4.0))
+                .striped(true)
+                .show(ui, |ui| {
+                    for (display_name, color, round_trip_time, owner, is_disconnected) in players {
+                        let [r, g, b] = color.0.to_srgba().to_u8_array_no_alpha();
+                        let (swatch, _) =
+                            ui.allocate_exact_size(egui::vec2(12.0, 12.0), egui::Sense::hover());
+                        ui.painter()
+                            .rect_filled(swatch, 2.0, egui::Color32::from_rgb(r, g, b));
+
+                        let mut name = egui::RichText::new(&display_name.0);
+                        if client_id.as_ref().is_some_and(|id| id.0 == owner.client_id) {
+                            name = name.strong();
+                        }
+                        ui.label(name);
+
+                        if is_disconnected {
+                            ui.label(egui::RichText::new("reconnecting").italics());
+                        } else {
+                            ui.label(format!("{} ms", round_trip_time.millis));
+                        }
+                        ui.end_row();
+                    }
+                });
+        });
+}
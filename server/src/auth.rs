@@ -0,0 +1,137 @@
+//! Helpers for running the server in secure mode, where every client must present a connect token
+//! signed with the server's private key.
+//!
+//! Keys and tokens are passed around as hexadecimal strings, such that they can be pasted into a
+//! text box or stored in a plain text file.
+
+use std::{
+    fmt::{self, Display},
+    fs, io,
+    net::SocketAddr,
+    path::Path,
+    time::SystemTime,
+};
+
+use bevy_renet::netcode::{
+    ConnectToken, NETCODE_KEY_BYTES, NetcodeError, TokenGenerationError, generate_random_bytes,
+};
+use imm_sim_shared::PROTOCOL_ID_V0_1;
+
+/// How long, in seconds, a freshly issued connect token may be used to start a connection.
+pub const DEFAULT_TOKEN_EXPIRY_SECS: u64 = 300;
+
+/// How long, in seconds, a connection made with a connect token may go without hearing from the
+/// other side before timing out.
+const TOKEN_TIMEOUT_SECS: i32 = 15;
+
+/// A private key shared by the server and whatever issues its connect tokens.
+pub type PrivateKey = [u8; NETCODE_KEY_BYTES];
+
+#[derive(Debug)]
+pub enum AuthError {
+    Io(io::Error),
+    InvalidHex,
+    InvalidKeyLength(usize),
+    InvalidToken(NetcodeError),
+    TokenGeneration(TokenGenerationError),
+}
+
+impl Display for AuthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "{e}"),
+            Self::InvalidHex => write!(f, "expected a hexadecimal string"),
+            Self::InvalidKeyLength(len) => write!(
+                f,
+                "expected a private key of {NETCODE_KEY_BYTES} bytes, but found {len} bytes"
+            ),
+            Self::InvalidToken(e) => write!(f, "invalid connect token: {e}"),
+            Self::TokenGeneration(e) => write!(f, "could not generate connect token: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for AuthError {}
+
+impl From<io::Error> for AuthError {
+    fn from(value: io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+/// Generate a new random private key.
+pub fn generate_private_key() -> PrivateKey {
+    generate_random_bytes()
+}
+
+/// Parse a private key from its hexadecimal form.
+pub fn parse_private_key(hex: &str) -> Result<PrivateKey, AuthError> {
+    let bytes = decode_hex(hex)?;
+    let len = bytes.len();
+
+    bytes
+        .try_into()
+        .map_err(|_| AuthError::InvalidKeyLength(len))
+}
+
+/// Read a private key from a file holding its hexadecimal form.
+pub fn read_private_key(path: &Path) -> Result<PrivateKey, AuthError> {
+    parse_private_key(&fs::read_to_string(path)?)
+}
+
+/// Mint a connect token for the given client, usable on any of the given server addresses, and
+/// return it in its hexadecimal form.
+pub fn issue_connect_token(
+    private_key: &PrivateKey,
+    client_id: u64,
+    server_addresses: Vec<SocketAddr>,
+    expire_seconds: u64,
+) -> Result<String, AuthError> {
+    let current_time = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .expect("System time is less than Unix epoch");
+
+    let token = ConnectToken::generate(
+        current_time,
+        PROTOCOL_ID_V0_1,
+        expire_seconds,
+        client_id,
+        TOKEN_TIMEOUT_SECS,
+        server_addresses,
+        None,
+        private_key,
+    )
+    .map_err(AuthError::TokenGeneration)?;
+
+    let mut bytes = Vec::new();
+    token.write(&mut bytes)?;
+
+    Ok(encode_hex(&bytes))
+}
+
+/// Parse a connect token from its hexadecimal form.
+pub fn parse_connect_token(hex: &str) -> Result<ConnectToken, AuthError> {
+    let bytes = decode_hex(hex)?;
+    ConnectToken::read(&mut bytes.as_slice()).map_err(AuthError::InvalidToken)
+}
+
+pub fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Decode a hexadecimal string, ignoring any surrounding whitespace.
+pub fn decode_hex(hex: &str) -> Result<Vec<u8>, AuthError> {
+    let hex = hex.trim();
+    if hex.len() % 2 != 0 {
+        return Err(AuthError::InvalidHex);
+    }
+
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| {
+            hex.get(i..i + 2)
+                .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+                .ok_or(AuthError::InvalidHex)
+        })
+        .collect()
+}
@@ -0,0 +1,265 @@
+//! Bans, keyed by IP address or display name, which may be kept in a file such that they outlive
+//! the server process.
+//!
+//! The ban file is written in RON, as a list of [`Ban`]s:
+//!
+//! ```ron
+//! [
+//!     (target: DisplayName("griefer"), reason: Some("Spawn camping")),
+//!     (target: Ip("203.0.113.7"), expires_at: Some(1767225600)),
+//! ]
+//! ```
+
+use std::{
+    fmt::{self, Display},
+    fs, io,
+    net::IpAddr,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use bevy::prelude::*;
+use imm_sim_shared::handshake::HandshakeRejection;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum BanTarget {
+    Ip(IpAddr),
+    DisplayName(String),
+}
+
+impl Display for BanTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Ip(ip) => write!(f, "IP {ip}"),
+            Self::DisplayName(display_name) => write!(f, "display name `{display_name}`"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Ban {
+    pub target: BanTarget,
+
+    #[serde(default)]
+    pub reason: Option<String>,
+
+    /// Seconds since the Unix epoch at which the ban lifts. Bans without one are permanent.
+    #[serde(default)]
+    pub expires_at: Option<u64>,
+}
+
+impl Ban {
+    pub fn is_expired(&self, now: u64) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= now)
+    }
+
+    /// The reason given to a client turned away by this ban.
+    pub fn rejection(&self) -> HandshakeRejection {
+        let now = unix_time_now();
+
+        HandshakeRejection::Banned {
+            reason: self.reason.clone(),
+            remaining_secs: self
+                .expires_at
+                .map(|expires_at| expires_at.saturating_sub(now)),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum BanError {
+    Io(io::Error),
+    Parse(ron::error::SpannedError),
+    Serialize(ron::Error),
+}
+
+impl Display for BanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "{e}"),
+            Self::Parse(e) => write!(f, "could not parse ban file: {e}"),
+            Self::Serialize(e) => write!(f, "could not serialize ban list: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for BanError {}
+
+/// Every active ban, alongside the file they are saved to, if any.
+#[derive(Default, Resource)]
+pub struct BanList {
+    bans: Vec<Ban>,
+    path: Option<PathBuf>,
+}
+
+impl BanList {
+    /// Load the bans held in the file at the given path, which is then kept up to date with any
+    /// changes. A file that does not exist yet is treated as holding no bans.
+    pub fn load(path: &Path) -> Result<Self, BanError> {
+        let bans = match fs::read_to_string(path) {
+            Ok(contents) => ron::from_str(&contents).map_err(BanError::Parse)?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(BanError::Io(e)),
+        };
+
+        let mut list = Self {
+            bans,
+            path: Some(path.to_owned()),
+        };
+        list.bans.retain(|ban| !ban.is_expired(unix_time_now()));
+
+        Ok(list)
+    }
+
+    /// Write the bans to the file they were loaded from. Does nothing if there is no such file.
+    pub fn save(&self) -> Result<(), BanError> {
+        let Some(path) = self.path.as_ref() else {
+            return Ok(());
+        };
+
+        let contents = ron::ser::to_string_pretty(&self.bans, ron::ser::PrettyConfig::default())
+            .map_err(BanError::Serialize)?;
+
+        fs::write(path, contents).map_err(BanError::Io)
+    }
+
+    /// Add a ban, replacing any existing ban on the same target.
+    pub fn add(&mut self, ban: Ban) {
+        self.remove(&ban.target);
+        self.bans.push(ban);
+    }
+
+    /// Lift the ban on the given target, returning `false` if there was none.
+    pub fn remove(&mut self, target: &BanTarget) -> bool {
+        let len = self.bans.len();
+        self.bans.retain(|ban| &ban.target != target);
+
+        self.bans.len() != len
+    }
+
+    /// Find an active ban on either the given IP address or display name.
+    pub fn find(&self, ip: Option<IpAddr>, display_name: Option<&str>) -> Option<&Ban> {
+        let now = unix_time_now();
+
+        self.bans
+            .iter()
+            .filter(|ban| !ban.is_expired(now))
+            .find(|ban| match &ban.target {
+                BanTarget::Ip(banned_ip) => ip == Some(*banned_ip),
+                BanTarget::DisplayName(banned_name) => display_name == Some(banned_name.as_str()),
+            })
+    }
+
+    /// Every active ban, in the order they were added.
+    pub fn iter(&self) -> impl Iterator<Item = &Ban> {
+        let now = unix_time_now();
+        self.bans.iter().filter(move |ban| !ban.is_expired(now))
+    }
+}
+
+pub fn unix_time_now() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .expect("System time is less than Unix epoch")
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+
+    use super::*;
+
+    const IP: IpAddr = IpAddr::V4(Ipv4Addr::new(203, 0, 113, 7));
+
+    fn ban(target: BanTarget, expires_at: Option<u64>) -> Ban {
+        Ban {
+            target,
+            reason: None,
+            expires_at,
+        }
+    }
+
+    #[test]
+    fn finds_bans_by_ip_or_display_name() {
+        let mut list = BanList::default();
+        list.add(ban(BanTarget::Ip(IP), None));
+        list.add(ban(BanTarget::DisplayName("griefer".to_owned()), None));
+
+        let by_ip = list.find(Some(IP), Some("someone"));
+        assert_eq!(by_ip.map(|ban| &ban.target), Some(&BanTarget::Ip(IP)));
+
+        let by_name = list.find(None, Some("griefer"));
+        assert_eq!(
+            by_name.map(|ban| &ban.target),
+            Some(&BanTarget::DisplayName("griefer".to_owned()))
+        );
+
+        let other_ip = IpAddr::V4(Ipv4Addr::LOCALHOST);
+        assert!(list.find(Some(other_ip), Some("someone")).is_none());
+        assert!(list.find(None, None).is_none());
+    }
+
+    #[test]
+    fn expired_bans_are_ignored() {
+        let mut list = BanList::default();
+        list.add(ban(BanTarget::Ip(IP), Some(1)));
+
+        assert!(list.find(Some(IP), None).is_none());
+        assert_eq!(list.iter().count(), 0);
+    }
+
+    #[test]
+    fn permanent_and_future_bans_are_active() {
+        let mut list = BanList::default();
+        list.add(ban(BanTarget::Ip(IP), None));
+        list.add(ban(
+            BanTarget::DisplayName("griefer".to_owned()),
+            Some(u64::MAX),
+        ));
+
+        assert!(list.find(Some(IP), None).is_some());
+        assert!(list.find(None, Some("griefer")).is_some());
+        assert_eq!(list.iter().count(), 2);
+    }
+
+    #[test]
+    fn adding_replaces_and_removing_lifts() {
+        let mut list = BanList::default();
+        list.add(ban(BanTarget::Ip(IP), None));
+        list.add(ban(BanTarget::Ip(IP), Some(u64::MAX)));
+
+        assert_eq!(list.iter().count(), 1);
+        assert_eq!(
+            list.find(Some(IP), None).and_then(|ban| ban.expires_at),
+            Some(u64::MAX)
+        );
+
+        assert!(list.remove(&BanTarget::Ip(IP)));
+        assert!(!list.remove(&BanTarget::Ip(IP)));
+        assert!(list.find(Some(IP), None).is_none());
+    }
+
+    #[test]
+    fn rejection_counts_down_from_the_server_clock() {
+        let permanent = ban(BanTarget::Ip(IP), None).rejection();
+        assert_eq!(
+            permanent,
+            HandshakeRejection::Banned {
+                reason: None,
+                remaining_secs: None,
+            }
+        );
+
+        let expires_at = unix_time_now() + 600;
+        let HandshakeRejection::Banned {
+            remaining_secs: Some(remaining_secs),
+            ..
+        } = ban(BanTarget::Ip(IP), Some(expires_at)).rejection()
+        else {
+            panic!("Expected a temporary ban");
+        };
+        assert!((599..=600).contains(&remaining_secs));
+    }
+}
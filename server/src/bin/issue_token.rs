@@ -0,0 +1,95 @@
+//! A small command line tool for creating private keys and minting connect tokens for a server
+//! running in secure mode.
+
+use std::{fs, net::SocketAddr, path::PathBuf, process::ExitCode};
+
+use clap::{Parser, Subcommand};
+use imm_sim_server::auth::{self, DEFAULT_TOKEN_EXPIRY_SECS};
+
+#[derive(Parser)]
+struct Args {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Generate a new private key, to be shared between the server and this tool.
+    Keygen {
+        /// Where to write the key.
+        out: PathBuf,
+    },
+
+    /// Mint a connect token for a single client.
+    Token {
+        /// The file holding the server's private key.
+        #[arg(long)]
+        private_key_file: PathBuf,
+
+        /// The address(es) of the server the token is valid for.
+        #[arg(long = "server", required = true)]
+        server_addresses: Vec<SocketAddr>,
+
+        /// The netcode client ID to embed in the token. A random one is used if not given.
+        #[arg(long)]
+        client_id: Option<u64>,
+
+        /// How many seconds the token may be used for.
+        #[arg(long, default_value_t = DEFAULT_TOKEN_EXPIRY_SECS)]
+        expire_seconds: u64,
+
+        /// Where to write the token. It is printed to stdout if not given.
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+}
+
+fn main() -> ExitCode {
+    match run(Args::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: Args) -> Result<(), Box<dyn std::error::Error>> {
+    match args.command {
+        Command::Keygen { out } => {
+            let key = auth::generate_private_key();
+            fs::write(&out, auth::encode_hex(&key))?;
+            println!("Wrote a new private key to {}.", out.display());
+        }
+
+        Command::Token {
+            private_key_file,
+            server_addresses,
+            client_id,
+            expire_seconds,
+            out,
+        } => {
+            let private_key = auth::read_private_key(&private_key_file)?;
+            let client_id = client_id.unwrap_or_else(rand::random);
+            let token = auth::issue_connect_token(
+                &private_key,
+                client_id,
+                server_addresses,
+                expire_seconds,
+            )?;
+
+            match out {
+                Some(path) => {
+                    fs::write(&path, token)?;
+                    println!(
+                        "Wrote a connect token for client {client_id} to {}.",
+                        path.display()
+                    );
+                }
+                None => println!("{token}"),
+            }
+        }
+    }
+
+    Ok(())
+}
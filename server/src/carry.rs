@@ -0,0 +1,232 @@
+use avian3d::prelude::*;
+use bevy::prelude::*;
+use bevy_replicon::prelude::*;
+use imm_sim_shared::{
+    carry::{C2SThrow, Carried, MAX_CARRY_MASS, hold_translation},
+    interaction::{Interactable, eye_translation},
+    physics::components::{collision::carried_collision_layers, movement::Crouching},
+    player::components::{Disconnected, LookDirection},
+};
+
+use crate::{ServerState, connection::tracking::ConnectionTracker, interaction::PlayerInteracted};
+
+/// How far, in metres, from an object a player may pick it up.
+const CARRY_RANGE: f32 = 2.0;
+
+/// The fastest a carried object is moved towards where it is held, in metres per second.
+const MAX_HOLD_SPEED: f32 = 10.0;
+
+/// Should a carried object end up further than this from where it is held, in metres, it is
+/// counted as caught on something.
+const OBSTRUCTED_DISTANCE: f32 = 0.75;
+
+/// How long a carried object may stay caught on something, in seconds, before it is dropped.
+const MAX_OBSTRUCTED_SECS: f32 = 0.5;
+
+/// The impulse given to a thrown object, in newton-seconds, such that lighter objects fly further.
+const THROW_IMPULSE: f32 = 20.0;
+
+/// The fastest an object can be thrown, in metres per second, however light it is.
+const MAX_THROW_SPEED: f32 = 15.0;
+
+/// Lets players pick up [`Carryable`] objects by using them, carry them about in front of them, and
+/// drop or throw them.
+pub struct ServerCarryPlugin;
+
+impl Plugin for ServerCarryPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (use_carryables, throw_carried_objects)
+                .chain()
+                .run_if(in_state(ServerState::Running)),
+        )
+        .add_systems(
+            FixedUpdate,
+            hold_carried_objects.run_if(in_state(ServerState::Running)),
+        );
+    }
+}
+
+/// A dynamic body that players can pick up, should it be light enough.
+#[derive(Clone, Component, Copy, Debug)]
+pub struct Carryable;
+
+/// How long a carried object has been caught on something.
+#[derive(Component, Default)]
+struct Grip {
+    obstructed_secs: f32,
+}
+
+/// The components that make a body of the given mass carryable.
+pub fn carryable(mass: f32) -> (Carryable, Interactable) {
+    let prompt = if mass <= MAX_CARRY_MASS {
+        "Pick up"
+    } else {
+        "Too heavy to lift"
+    };
+
+    (
+        Carryable,
+        Interactable {
+            prompt: prompt.to_owned(),
+            range: CARRY_RANGE,
+        },
+    )
+}
+
+fn grab(entity: Entity, carrier: Entity, interactable: &mut Interactable, commands: &mut Commands) {
+    commands.entity(entity).insert((
+        Carried { carrier },
+        Grip::default(),
+        GravityScale(0.0),
+        carried_collision_layers(),
+    ));
+    interactable.prompt = "Drop".to_owned();
+}
+
+fn release(entity: Entity, interactable: &mut Interactable, commands: &mut Commands) {
+    commands
+        .entity(entity)
+        .remove::<(Carried, Grip, GravityScale, CollisionLayers)>();
+    interactable.prompt = "Pick up".to_owned();
+}
+
+/// Pick up whatever [`Carryable`] a player uses, or drop it should they already be carrying it.
+fn use_carryables(
+    mut reader: EventReader<PlayerInteracted>,
+    mut carryables: Query<(&mut Interactable, &Mass, Option<&Carried>), With<Carryable>>,
+    mut commands: Commands,
+) {
+    // Who carries what, kept up to date as objects are picked up and dropped below, since the
+    // commands doing so only take effect afterwards.
+    let mut carriers = carryables
+        .iter()
+        .filter_map(|(.., carried)| carried.map(|carried| carried.carrier))
+        .collect::<Vec<_>>();
+    let mut handled = Vec::new();
+
+    for PlayerInteracted {
+        client_id,
+        player,
+        target,
+    } in reader.read()
+    {
+        let Ok((mut interactable, mass, carried)) = carryables.get_mut(*target) else {
+            continue;
+        };
+
+        // Two players may have used the same object at once, and the first got there first.
+        if handled.contains(target) {
+            continue;
+        }
+
+        match carried {
+            Some(carried) if carried.carrier == *player => {
+                release(*target, &mut interactable, &mut commands);
+                carriers.retain(|carrier| carrier != player);
+            }
+            Some(_) => debug!("Player {client_id} tried to take {target} from another player."),
+            None if carriers.contains(player) => {
+                debug!("Player {client_id} is already carrying something.")
+            }
+            None if mass.0 > MAX_CARRY_MASS => debug!("{target} is too heavy to be carried."),
+            None => {
+                grab(*target, *player, &mut interactable, &mut commands);
+                carriers.push(*player);
+            }
+        }
+
+        handled.push(*target);
+    }
+}
+
+fn throw_carried_objects(
+    mut reader: EventReader<FromClient<C2SThrow>>,
+    conn_tracker: Res<ConnectionTracker>,
+    players: Query<(&LookDirection, &LinearVelocity), Without<Carried>>,
+    mut carried: Query<(
+        Entity,
+        &Carried,
+        &Mass,
+        &mut LinearVelocity,
+        &mut Interactable,
+    )>,
+    mut commands: Commands,
+) {
+    for FromClient { client_id, .. } in reader.read() {
+        let client_id = client_id.get();
+        let Some(player) = conn_tracker.get_avatar(client_id) else {
+            debug!("Unexpected throw from client {client_id}. This client is not tracked.");
+            continue;
+        };
+
+        let Ok((look, player_lin_vel)) = players.get(player) else {
+            error!("Player {client_id}'s avatar is missing a component.");
+            continue;
+        };
+
+        // The client may have lost its grip before the server heard about the throw.
+        let Some((entity, _, mass, mut lin_vel, mut interactable)) = carried
+            .iter_mut()
+            .find(|(_, carried, ..)| carried.carrier == player)
+        else {
+            continue;
+        };
+
+        let throw_speed = (THROW_IMPULSE / mass.0).min(MAX_THROW_SPEED);
+        lin_vel.0 = player_lin_vel.0 + look.direction() * throw_speed;
+
+        release(entity, &mut interactable, &mut commands);
+    }
+}
+
+/// Move every carried object towards where its carrier holds it, dropping any that have been caught
+/// on something for too long, or whose carrier is gone.
+///
+/// Like doors, carried objects are moved by their velocity, such that they still collide with the
+/// level rather than passing through it.
+fn hold_carried_objects(
+    time: Res<Time>,
+    carriers: Query<(&Transform, &LookDirection, Has<Crouching>), Without<Disconnected>>,
+    mut carried: Query<(
+        Entity,
+        &Carried,
+        &mut Grip,
+        &Position,
+        &mut LinearVelocity,
+        &mut AngularVelocity,
+        &mut Interactable,
+    )>,
+    mut commands: Commands,
+) {
+    let delta_secs = time.delta_secs();
+    if delta_secs <= 0.0 {
+        return;
+    }
+
+    for (entity, carried, mut grip, position, mut lin_vel, mut ang_vel, mut interactable) in
+        carried.iter_mut()
+    {
+        let Ok((transform, look, is_crouching)) = carriers.get(carried.carrier) else {
+            release(entity, &mut interactable, &mut commands);
+            continue;
+        };
+
+        let eye = eye_translation(transform.translation, is_crouching);
+        let offset = hold_translation(eye, look.direction()) - position.0;
+
+        if offset.length() > OBSTRUCTED_DISTANCE {
+            grip.obstructed_secs += delta_secs;
+            if grip.obstructed_secs > MAX_OBSTRUCTED_SECS {
+                release(entity, &mut interactable, &mut commands);
+                continue;
+            }
+        } else {
+            grip.obstructed_secs = 0.0;
+        }
+
+        lin_vel.0 = (offset / delta_secs).clamp_length_max(MAX_HOLD_SPEED);
+        ang_vel.0 = Vec3::ZERO;
+    }
+}
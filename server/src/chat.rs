@@ -0,0 +1,130 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    time::Duration,
+};
+
+use bevy::prelude::*;
+use bevy_replicon::prelude::*;
+use imm_sim_shared::chat::{C2SChatMessage, ChatSender, MAX_CHAT_MESSAGE_LEN, S2CChatMessage};
+
+use crate::{ServerState, connection::tracking::ConnectionTracker};
+
+/// The most chat messages a client may send within [`CHAT_RATE_WINDOW`].
+const CHAT_RATE_LIMIT: usize = 5;
+
+const CHAT_RATE_WINDOW: Duration = Duration::from_secs(5);
+
+/// Passes chat messages between players, turning away those which are too long or sent too often.
+pub struct ServerChatPlugin;
+
+impl Plugin for ServerChatPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ChatRateLimiter>().add_systems(
+            Update,
+            relay_chat_messages.run_if(in_state(ServerState::Running)),
+        );
+    }
+}
+
+/// A chat message from the server to every connected player, such as for someone joining.
+pub fn system_message(message: String) -> ToClients<S2CChatMessage> {
+    ToClients {
+        mode: SendMode::Broadcast,
+        event: S2CChatMessage {
+            sender: ChatSender::System,
+            message,
+        },
+    }
+}
+
+/// When each client's recent chat messages were sent, oldest first.
+#[derive(Default, Resource)]
+struct ChatRateLimiter {
+    recent: HashMap<u64, VecDeque<Duration>>,
+}
+
+impl ChatRateLimiter {
+    /// Record a message from the given client, unless it has already sent too many recently.
+    fn try_send(&mut self, client_id: u64, now: Duration) -> bool {
+        let sent = self.recent.entry(client_id).or_default();
+        if sent.len() >= CHAT_RATE_LIMIT {
+            return false;
+        }
+
+        sent.push_back(now);
+        true
+    }
+
+    /// Forget any messages sent before the current window.
+    fn forget_before(&mut self, now: Duration) {
+        self.recent.retain(|_, sent| {
+            while sent
+                .front()
+                .is_some_and(|sent_at| now.saturating_sub(*sent_at) >= CHAT_RATE_WINDOW)
+            {
+                sent.pop_front();
+            }
+
+            !sent.is_empty()
+        });
+    }
+}
+
+fn relay_chat_messages(
+    mut reader: EventReader<FromClient<C2SChatMessage>>,
+    mut writer: EventWriter<ToClients<S2CChatMessage>>,
+    time: Res<Time<Real>>,
+    conn_tracker: Res<ConnectionTracker>,
+    mut rate_limiter: ResMut<ChatRateLimiter>,
+) {
+    let now = time.elapsed();
+    rate_limiter.forget_before(now);
+
+    for FromClient {
+        client_id,
+        event: C2SChatMessage { message },
+    } in reader.read()
+    {
+        // Only players who have completed the handshake may chat.
+        let Some(display_name) = conn_tracker.get_display_name(client_id.get()) else {
+            continue;
+        };
+
+        let mut refuse = |reason: &str| {
+            writer.send(ToClients {
+                mode: SendMode::Direct(*client_id),
+                event: S2CChatMessage {
+                    sender: ChatSender::System,
+                    message: reason.to_owned(),
+                },
+            });
+        };
+
+        let message = message.trim();
+        if message.is_empty() {
+            continue;
+        }
+
+        if message.chars().count() > MAX_CHAT_MESSAGE_LEN {
+            refuse(&format!(
+                "Your message was not sent, as it is longer than {MAX_CHAT_MESSAGE_LEN} characters."
+            ));
+            continue;
+        }
+
+        if !rate_limiter.try_send(client_id.get(), now) {
+            refuse("Your message was not sent, as you are sending messages too quickly.");
+            continue;
+        }
+
+        info!("<{display_name}> {message}");
+
+        writer.send(ToClients {
+            mode: SendMode::Broadcast,
+            event: S2CChatMessage {
+                sender: ChatSender::Player(display_name.to_owned()),
+                message: message.to_owned(),
+            },
+        });
+    }
+}
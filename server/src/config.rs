@@ -0,0 +1,297 @@
+//! Server settings, read from a TOML file.
+//!
+//! ```toml
+//! [network]
+//! bind_addr = "0.0.0.0:5000"
+//! max_clients = 16
+//!
+//! [gameplay]
+//! movement_acceleration = 30.0
+//! spawn_radius = 1.5
+//!
+//! [logging]
+//! filter = "info,imm_sim_server=debug"
+//! network_stats_interval_secs = 30.0
+//! ```
+//!
+//! Every setting is optional. The `[gameplay]` and `[logging]` settings can be reloaded while the
+//! server runs, while the `[network]` settings, the level and the tick rate are only read when it
+//! starts.
+
+use std::{
+    fmt::{self, Display},
+    fs, io,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use bevy::{
+    log::{
+        BoxedLayer,
+        tracing_subscriber::{EnvFilter, Registry, reload},
+    },
+    prelude::*,
+};
+use imm_sim_shared::{
+    FIXED_TIMESTEP_HZ, level::DEFAULT_LEVEL_PATH,
+    physics::components::movement::MovementAcceleration, player::components::Player,
+};
+use serde::Deserialize;
+
+use crate::HandshakeLimits;
+
+/// The most clients that may be connected at once, unless configured otherwise.
+pub const DEFAULT_MAX_CLIENTS: usize = 32;
+
+/// The log filter used until one is configured.
+const DEFAULT_LOG_FILTER: &str = "info,wgpu=error,naga=warn";
+
+/// Keeps the [`GameplayConfig`] and [`LoggingConfig`] resources applied to the running server, and
+/// reloads them from the config file on a [`ReloadConfig`] event.
+pub struct ServerConfigPlugin;
+
+impl Plugin for ServerConfigPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ConfigPath>()
+            .init_resource::<GameplayConfig>()
+            .init_resource::<LoggingConfig>()
+            .add_event::<ReloadConfig>()
+            .add_systems(
+                Update,
+                (
+                    reload_config.run_if(on_event::<ReloadConfig>),
+                    apply_gameplay_config.run_if(resource_changed::<GameplayConfig>),
+                    apply_logging_config.run_if(resource_changed::<LoggingConfig>),
+                )
+                    .chain(),
+            );
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    pub network: NetworkConfig,
+    pub gameplay: GameplayConfig,
+    pub logging: LoggingConfig,
+}
+
+/// Settings only read when the server starts.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct NetworkConfig {
+    pub bind_addr: Option<SocketAddr>,
+    pub max_clients: usize,
+    pub room_password: Option<String>,
+
+    /// See [`ServerLifecycleCmd::StartServer`](crate::ServerLifecycleCmd::StartServer).
+    pub private_key_file: Option<PathBuf>,
+    /// See [`ServerLifecycleCmd::StartServer`](crate::ServerLifecycleCmd::StartServer). Unset by
+    /// default, such that bans are only kept on disk once a file is configured.
+    pub ban_file: Option<PathBuf>,
+
+    /// See [`HandshakeLimits`].
+    pub handshake_timeout_secs: f32,
+    pub max_pending_handshakes: usize,
+}
+
+impl NetworkConfig {
+    /// The configured [`HandshakeLimits`], or `None` if the timeout is negative or not a number.
+    pub fn handshake_limits(&self) -> Option<HandshakeLimits> {
+        Some(HandshakeLimits {
+            timeout: Duration::try_from_secs_f32(self.handshake_timeout_secs).ok()?,
+            max_pending: self.max_pending_handshakes,
+        })
+    }
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        Self {
+            bind_addr: None,
+            max_clients: DEFAULT_MAX_CLIENTS,
+            room_password: None,
+            private_key_file: None,
+            ban_file: None,
+            handshake_timeout_secs: HandshakeLimits::default().timeout.as_secs_f32(),
+            max_pending_handshakes: HandshakeLimits::default().max_pending,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Resource)]
+#[serde(default, deny_unknown_fields)]
+pub struct GameplayConfig {
+    /// The level file to load. Clients must have the same level to join, so this is only read when
+    /// the server starts.
+    pub level: PathBuf,
+
+    /// The rate of the fixed time-step. Clients are told this on joining and run their own
+    /// prediction at the same rate, so this is only read when the server starts.
+    pub tick_rate_hz: f64,
+
+    pub movement_acceleration: f32,
+
+    /// How far from the chosen spawn point, horizontally, a player may be placed.
+    pub spawn_radius: f32,
+
+    /// How long, in seconds, a disconnected player's avatar is kept for them to reconnect to.
+    pub reconnect_grace_secs: f32,
+}
+
+impl Default for GameplayConfig {
+    fn default() -> Self {
+        Self {
+            level: PathBuf::from(DEFAULT_LEVEL_PATH),
+            tick_rate_hz: FIXED_TIMESTEP_HZ,
+            movement_acceleration: MovementAcceleration::default().0,
+            spawn_radius: 0.0,
+            reconnect_grace_secs: 60.0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Resource)]
+#[serde(default, deny_unknown_fields)]
+pub struct LoggingConfig {
+    /// A filter in the same form as `RUST_LOG`, like `info,imm_sim_server=debug`.
+    pub filter: String,
+
+    /// How often, in seconds, every client's round-trip time, packet loss and bandwidth is logged.
+    /// Zero turns this off.
+    pub network_stats_interval_secs: f32,
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self {
+            filter: DEFAULT_LOG_FILTER.to_owned(),
+            network_stats_interval_secs: 60.0,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(io::Error),
+    Parse(toml::de::Error),
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "could not read config file: {e}"),
+            Self::Parse(e) => write!(f, "could not parse config file: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl ServerConfig {
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        let contents = fs::read_to_string(path).map_err(ConfigError::Io)?;
+        toml::from_str(&contents).map_err(ConfigError::Parse)
+    }
+}
+
+/// The file the config was loaded from, if any, which it is reloaded from on [`ReloadConfig`].
+#[derive(Default, Resource)]
+pub struct ConfigPath(pub Option<PathBuf>);
+
+/// Reload the `[gameplay]` and `[logging]` settings from the config file.
+#[derive(Event)]
+pub struct ReloadConfig;
+
+/// Lets the log filter be swapped out while the app runs. Only present on the standalone server,
+/// which sets up logging through [`reloadable_log_filter`].
+#[derive(Resource)]
+struct LogFilterHandle(reload::Handle<EnvFilter, Registry>);
+
+/// A [`LogPlugin::custom_layer`](bevy::log::LogPlugin::custom_layer) that filters logs by the
+/// [`LoggingConfig`]. The [`LogPlugin`](bevy::log::LogPlugin)'s own filter must let through
+/// everything this one might.
+pub(crate) fn reloadable_log_filter(app: &mut App) -> Option<BoxedLayer> {
+    let (layer, handle) = reload::Layer::new(EnvFilter::new(DEFAULT_LOG_FILTER));
+    app.insert_resource(LogFilterHandle(handle));
+
+    Some(Box::new(layer))
+}
+
+fn reload_config(
+    path: Res<ConfigPath>,
+    mut gameplay: ResMut<GameplayConfig>,
+    mut logging: ResMut<LoggingConfig>,
+) {
+    let Some(path) = path.0.as_deref() else {
+        error!("There is no config file to reload.");
+        return;
+    };
+
+    let config = match ServerConfig::load(path) {
+        Ok(config) => config,
+        Err(e) => {
+            error!(
+                "Error reloading `{}`, keeping the current settings: {e}",
+                path.display()
+            );
+            return;
+        }
+    };
+
+    // Only mark the resources as changed should something actually differ. Clients already in the
+    // game have been told the tick rate, so it is kept along with the level.
+    let level = gameplay.level.clone();
+    let tick_rate_hz = gameplay.tick_rate_hz;
+    gameplay.set_if_neq(GameplayConfig {
+        level,
+        tick_rate_hz,
+        ..config.gameplay
+    });
+    logging.set_if_neq(config.logging);
+
+    info!(
+        "Reloaded `{}`. Changes to the [network] settings, the level or the tick rate need a \
+         restart.",
+        path.display()
+    );
+}
+
+fn apply_gameplay_config(
+    config: Res<GameplayConfig>,
+    mut time: ResMut<Time<Fixed>>,
+    mut players: Query<&mut MovementAcceleration, With<Player>>,
+) {
+    if !config.tick_rate_hz.is_finite() || config.tick_rate_hz <= 0.0 {
+        error!(
+            "Ignoring the tick rate of {} Hz, as it must be positive.",
+            config.tick_rate_hz
+        );
+    } else {
+        time.set_timestep_hz(config.tick_rate_hz);
+    }
+
+    // The client predicts with whatever acceleration is replicated to its avatar.
+    for mut acceleration in players.iter_mut() {
+        acceleration.0 = config.movement_acceleration;
+    }
+}
+
+fn apply_logging_config(config: Res<LoggingConfig>, handle: Option<Res<LogFilterHandle>>) {
+    let Some(handle) = handle else {
+        return;
+    };
+
+    let filter = match EnvFilter::try_new(&config.filter) {
+        Ok(filter) => filter,
+        Err(e) => {
+            error!("Invalid log filter `{}`: {e}", config.filter);
+            return;
+        }
+    };
+
+    if let Err(e) = handle.0.reload(filter) {
+        error!("Could not apply the log filter: {e}");
+    }
+}
@@ -0,0 +1,51 @@
+use std::collections::HashSet;
+
+use bevy::prelude::*;
+use bevy_renet::renet::RenetServer;
+use bevy_replicon::prelude::*;
+use imm_sim_shared::disconnect::{DisconnectReason, S2CDisconnectNotice};
+
+/// Clients to be disconnected at the start of the next frame, such that any messages sent to them
+/// this frame, like the reason they are being disconnected, go out first.
+#[derive(Default, Resource)]
+pub struct ScheduledDisconnects {
+    client_ids: Vec<u64>,
+    /// Clients which the server has thrown out, whose avatars are not held for them to reconnect.
+    forced: HashSet<u64>,
+}
+
+impl ScheduledDisconnects {
+    pub fn schedule(&mut self, client_id: u64) {
+        self.client_ids.push(client_id);
+    }
+
+    /// Tell the client why it is being thrown out, such as for being kicked or banned, then
+    /// disconnect it. Its avatar is despawned rather than held for it to reconnect to.
+    pub fn force_disconnect(
+        &mut self,
+        client_id: u64,
+        reason: DisconnectReason,
+        writer: &mut EventWriter<ToClients<S2CDisconnectNotice>>,
+    ) {
+        writer.send(ToClients {
+            mode: SendMode::Direct(ClientId::new(client_id)),
+            event: S2CDisconnectNotice { reason },
+        });
+        self.forced.insert(client_id);
+        self.schedule(client_id);
+    }
+
+    /// Whether the given client was thrown out by the server, forgetting it in the process.
+    pub fn take_forced(&mut self, client_id: u64) -> bool {
+        self.forced.remove(&client_id)
+    }
+}
+
+pub fn process_scheduled_disconnects(
+    mut scheduled: ResMut<ScheduledDisconnects>,
+    mut server: ResMut<RenetServer>,
+) {
+    for client_id in scheduled.client_ids.drain(..) {
+        server.disconnect(client_id);
+    }
+}
@@ -1,42 +1,125 @@
-use std::collections::BTreeSet;
+use std::{collections::BTreeMap, time::Duration};
 
 use bevy::prelude::*;
+use bevy_renet::netcode::NetcodeServerTransport;
 use bevy_replicon::prelude::*;
 use imm_sim_shared::{
-    handshake::{C2SHandshakeStart, S2CHandshakeResult},
+    CRATE_VERSION,
+    chat::S2CChatMessage,
+    handshake::{C2SHandshakeStart, HandshakeRejection, S2CHandshakeResult},
+    level::LoadedLevel,
+    physics::components::movement::MovementAcceleration,
     player::SpawnPlayerCommandsExt,
 };
-use rand::{Rng, thread_rng};
+use rand::{Rng, seq::SliceRandom, thread_rng};
 
-use super::tracking::ConnectionTracker;
-use crate::RoomAuthentication;
+use super::{
+    disconnect::ScheduledDisconnects,
+    reconnect::{DisconnectedPlayers, SessionToken},
+    tracking::ConnectionTracker,
+};
+use crate::{
+    HandshakeLimits, RoomAuthentication, ban::BanList, chat::system_message, config::GameplayConfig,
+};
 
+/// Clients which have connected but are yet to complete the handshake, alongside when they
+/// connected.
 #[derive(Default, Resource)]
 pub struct AwaitingHandshakes {
-    set: BTreeSet<u64>,
+    pending: BTreeMap<u64, Duration>,
+}
+
+impl AwaitingHandshakes {
+    /// Whether the given client may send a handshake. The host of a listen server never connects
+    /// through the transport, so is always allowed to.
+    fn is_awaiting(&self, client_id: ClientId) -> bool {
+        client_id == ClientId::SERVER || self.pending.contains_key(&client_id.get())
+    }
 }
 
 pub fn handle_connection_events(
     mut reader: EventReader<ServerEvent>,
+    mut writer: EventWriter<ToClients<S2CHandshakeResult>>,
+    mut chat_writer: EventWriter<ToClients<S2CChatMessage>>,
 
+    time: Res<Time<Real>>,
+    ban_list: Res<BanList>,
+    limits: Res<HandshakeLimits>,
+    gameplay: Res<GameplayConfig>,
+    transport: Res<NetcodeServerTransport>,
     mut awaiting_handshakes: ResMut<AwaitingHandshakes>,
     mut conn_tracker: ResMut<ConnectionTracker>,
+    mut disconnected: ResMut<DisconnectedPlayers>,
+    mut scheduled_disconnects: ResMut<ScheduledDisconnects>,
+    session_tokens: Query<&SessionToken>,
 
     mut commands: Commands,
 ) {
     for event in reader.read() {
         match event {
             ServerEvent::ClientConnected { client_id } => {
+                let ip = transport.client_addr(client_id.get()).map(|addr| addr.ip());
+
+                // Clients from a banned IP are turned away before they can even send a handshake.
+                if let Some(ban) = ban_list.find(ip, None) {
+                    info!("Client {client_id:?} connected from banned {}.", ban.target);
+
+                    writer.send(ToClients {
+                        mode: SendMode::Direct(*client_id),
+                        event: S2CHandshakeResult::ConnectionRejected {
+                            reason: ban.rejection(),
+                        },
+                    });
+                    scheduled_disconnects.schedule(client_id.get());
+                    continue;
+                }
+
+                // Otherwise a flood of connections that never handshake could crowd out everyone.
+                if awaiting_handshakes.pending.len() >= limits.max_pending {
+                    warn!("Turning away client {client_id:?}, as too many handshakes are pending.");
+
+                    writer.send(ToClients {
+                        mode: SendMode::Direct(*client_id),
+                        event: S2CHandshakeResult::ConnectionRejected {
+                            reason: HandshakeRejection::ServerBusy,
+                        },
+                    });
+                    scheduled_disconnects.schedule(client_id.get());
+                    continue;
+                }
+
                 let client_id = client_id.get();
                 info!("Client {client_id} has successfully connected to the server.");
-                awaiting_handshakes.set.insert(client_id);
+                awaiting_handshakes
+                    .pending
+                    .insert(client_id, time.elapsed());
             }
             ServerEvent::ClientDisconnected { client_id, reason } => {
                 let client_id = client_id.get();
+                awaiting_handshakes.pending.remove(&client_id);
+                let was_forced = scheduled_disconnects.take_forced(client_id);
+
                 match conn_tracker.drop_connection(client_id) {
                     Some((avatar, display_name)) => {
                         info!("{display_name} disconnected from the server for {reason}.");
-                        commands.entity(avatar).despawn_recursive();
+                        chat_writer.send(system_message(format!("{display_name} left the game.")));
+
+                        // Hold on to the avatar for a while, in case its player comes back. Players
+                        // who were kicked, banned or otherwise thrown out don't get to.
+                        match session_tokens.get(avatar) {
+                            Ok(session_token)
+                                if !was_forced && gameplay.reconnect_grace_secs > 0.0 =>
+                            {
+                                disconnected.hold(
+                                    display_name,
+                                    avatar,
+                                    *session_token,
+                                    time.elapsed(),
+                                    &mut commands,
+                                );
+                            }
+                            _ => commands.entity(avatar).despawn_recursive(),
+                        }
                     }
 
                     None => {
@@ -51,72 +134,141 @@ pub fn handle_connection_events(
 pub fn handle_handshake_events(
     mut reader: EventReader<FromClient<C2SHandshakeStart>>,
     mut writer: EventWriter<ToClients<S2CHandshakeResult>>,
+    mut chat_writer: EventWriter<ToClients<S2CChatMessage>>,
 
     authentication: Res<RoomAuthentication>,
+    ban_list: Res<BanList>,
+    level: Res<LoadedLevel>,
+    gameplay: Res<GameplayConfig>,
+    fixed_time: Res<Time<Fixed>>,
+    transport: Res<NetcodeServerTransport>,
     mut awaiting_handshakes: ResMut<AwaitingHandshakes>,
     mut conn_tracker: ResMut<ConnectionTracker>,
+    mut disconnected: ResMut<DisconnectedPlayers>,
+    mut scheduled_disconnects: ResMut<ScheduledDisconnects>,
 
     mut commands: Commands,
 ) {
     for FromClient {
         client_id,
-        event: C2SHandshakeStart {
-            display_name,
-            room_password,
-        },
+        event:
+            C2SHandshakeStart {
+                display_name,
+                room_password,
+                crate_version,
+                content_hash: client_content_hash,
+                session_token,
+            },
     } in reader.read()
     {
+        let tick_rate_hz = fixed_time.timestep().as_secs_f64().recip();
+
+        // Either a second handshake from a client that has already joined, or one from a client
+        // that has already been rejected or timed out.
+        if !awaiting_handshakes.is_awaiting(*client_id) {
+            warn!("Ignoring an unexpected handshake from client {client_id:?}.");
+            continue;
+        }
+
+        let mut reject = |reason: HandshakeRejection| {
+            info!("Rejected handshake from client {client_id:?}: {reason}");
+
+            let event = S2CHandshakeResult::ConnectionRejected { reason };
+            let event = ToClients {
+                mode: SendMode::Direct(*client_id),
+                event,
+            };
+
+            writer.send(event);
+
+            // There is nothing more a rejected client can do, so free up its slot.
+            awaiting_handshakes.pending.remove(&client_id.get());
+            scheduled_disconnects.schedule(client_id.get());
+        };
+
+        // Clients built from a different version of the game, or with a different level loaded,
+        // would quietly desync from the server. Turn them away before anything else.
+        if crate_version != CRATE_VERSION {
+            reject(HandshakeRejection::VersionMismatch {
+                server: CRATE_VERSION.to_owned(),
+                client: crate_version.clone(),
+            });
+            continue;
+        }
+
+        if *client_content_hash != level.0.content_hash() {
+            reject(HandshakeRejection::ContentMismatch);
+            continue;
+        }
+
+        let ip = transport.client_addr(client_id.get()).map(|addr| addr.ip());
+        if let Some(ban) = ban_list.find(ip, Some(display_name)) {
+            reject(ban.rejection());
+            continue;
+        }
+
         // If there is a password, reject the client if no password was given with the handshake, or
         // if the password given is incorrect.
         if let RoomAuthentication::WithPassword(password) = authentication.as_ref() {
-            if let Some(password_attempt) = room_password {
-                if password_attempt != password {
-                    let event = S2CHandshakeResult::ConnectionRejected {
-                        reason: "The password you gave is not correct.".to_owned(),
-                    };
-                    let event = ToClients {
-                        mode: SendMode::Direct(*client_id),
-                        event,
-                    };
-
-                    writer.send(event);
+            match room_password {
+                Some(password_attempt) if password_attempt != password => {
+                    reject(HandshakeRejection::IncorrectPassword);
+                    continue;
+                }
+                Some(_) => {}
+                None => {
+                    reject(HandshakeRejection::PasswordRequired);
                     continue;
                 }
-            } else {
-                let event = S2CHandshakeResult::ConnectionRejected {
-                    reason: "This server requires a password to join.".to_owned(),
-                };
-                let event = ToClients {
-                    mode: SendMode::Direct(*client_id),
-                    event,
-                };
-
-                writer.send(event);
-                continue;
             }
         }
 
-        // Should the password not be required, or be correct, then ensure that the display name
-        // given is not already in use.
-        if let Some(_id) = conn_tracker.id_from_display_name(display_name.as_str()) {
-            let event = S2CHandshakeResult::ConnectionRejected {
-                reason: format!(
-                    "The requested display name `{display_name}`` is already in use on this server."
-                ),
+        // A player coming back within the grace period takes back their old avatar. Until then,
+        // nobody else may take their display name.
+        if disconnected.is_held(display_name) {
+            let reclaimed = session_token.and_then(|session_token| {
+                disconnected
+                    .reclaim(display_name, session_token, client_id.get(), &mut commands)
+                    .map(|avatar| (avatar, session_token))
+            });
+
+            let Some((avatar, session_token)) = reclaimed else {
+                reject(HandshakeRejection::DisplayNameInUse(display_name.clone()));
+                continue;
             };
-            let event = ToClients {
+
+            info!("{display_name} has reconnected and taken back their avatar.");
+
+            writer.send(ToClients {
                 mode: SendMode::Direct(*client_id),
-                event,
-            };
+                event: S2CHandshakeResult::ConnectionAccepted {
+                    client_id: client_id.get(),
+                    session_token,
+                    tick_rate_hz,
+                },
+            });
 
-            writer.send(event);
+            awaiting_handshakes.pending.remove(&client_id.get());
+            conn_tracker.track_connection(client_id.get(), avatar, display_name.clone());
+            chat_writer.send(system_message(format!("{display_name} rejoined the game.")));
+            continue;
+        }
+
+        // Should the password not be required, or be correct, then ensure that the display name
+        // given is not already in use.
+        if let Some(_id) = conn_tracker.id_from_display_name(display_name.as_str()) {
+            reject(HandshakeRejection::DisplayNameInUse(display_name.clone()));
             continue;
         }
 
         // If all checks pass, send the `ConnectionAccepted` response to the client, spawn a player
         // for this new connection, begin tracking and replicating all relevant information.
+        let session_token: u64 = thread_rng().r#gen();
+
         let event = S2CHandshakeResult::ConnectionAccepted {
             client_id: client_id.get(),
+            session_token,
+            tick_rate_hz,
         };
         let event = ToClients {
             mode: SendMode::Direct(*client_id),
@@ -125,15 +277,31 @@ pub fn handle_handshake_events(
 
         writer.send(event);
 
-        awaiting_handshakes.set.remove(&client_id.get());
+        awaiting_handshakes.pending.remove(&client_id.get());
 
-        let translation = {
+        // Levels are validated to have at least one spawn point when loaded.
+        let (translation, rotation) = {
             let mut rng = thread_rng();
-            let x: f32 = rng.gen_range(-20.0..20.0);
-            let y: f32 = 30.0;
-            let z: f32 = rng.gen_range(-20.0..20.0);
+            let spawn = level
+                .0
+                .spawn_points
+                .choose(&mut rng)
+                .expect("Level has no spawn points");
 
-            Vec3::new(x, y, z)
+            // Spread players out around the spawn point, such that they don't all land on top of
+            // one another.
+            let offset = if gameplay.spawn_radius > 0.0 {
+                let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+                let distance = gameplay.spawn_radius * rng.r#gen::<f32>().sqrt();
+                Vec3::new(angle.cos(), 0.0, angle.sin()) * distance
+            } else {
+                Vec3::ZERO
+            };
+
+            (
+                spawn.translation + offset,
+                Quat::from_axis_angle(Vec3::Y, spawn.yaw.to_radians()),
+            )
         };
 
         let color = {
@@ -150,11 +318,49 @@ pub fn handle_handshake_events(
                 client_id.get(),
                 display_name.clone(),
                 translation,
-                Quat::IDENTITY,
+                rotation,
                 color,
             )
+            .insert((
+                MovementAcceleration(gameplay.movement_acceleration),
+                SessionToken(session_token),
+            ))
             .id();
 
         conn_tracker.track_connection(client_id.get(), entity_id, display_name.clone());
+        chat_writer.send(system_message(format!("{display_name} joined the game.")));
     }
 }
+
+/// Disconnect any client that has taken longer than the [`HandshakeLimits`] allow to complete the
+/// handshake.
+pub fn expire_pending_handshakes(
+    mut writer: EventWriter<ToClients<S2CHandshakeResult>>,
+
+    time: Res<Time<Real>>,
+    limits: Res<HandshakeLimits>,
+    mut awaiting_handshakes: ResMut<AwaitingHandshakes>,
+    mut scheduled_disconnects: ResMut<ScheduledDisconnects>,
+) {
+    let now = time.elapsed();
+
+    awaiting_handshakes
+        .pending
+        .retain(|client_id, connected_at| {
+            if now.saturating_sub(*connected_at) < limits.timeout {
+                return true;
+            }
+
+            info!("Client {client_id} did not complete the handshake in time.");
+
+            writer.send(ToClients {
+                mode: SendMode::Direct(ClientId::new(*client_id)),
+                event: S2CHandshakeResult::ConnectionRejected {
+                    reason: HandshakeRejection::TimedOut,
+                },
+            });
+            scheduled_disconnects.schedule(*client_id);
+
+            false
+        });
+}
@@ -1,10 +1,21 @@
 use bevy::prelude::*;
+use bevy_replicon::server::ServerSet;
 
 use crate::ServerState;
 
-use self::handle_incoming::{handle_connection_events, handle_handshake_events};
+use self::{
+    disconnect::process_scheduled_disconnects,
+    handle_incoming::{
+        expire_pending_handshakes, handle_connection_events, handle_handshake_events,
+    },
+    reconnect::expire_disconnected_players,
+    stats::log_network_stats,
+};
 
+pub mod disconnect;
 pub mod handle_incoming;
+pub mod reconnect;
+pub mod stats;
 pub mod tracking;
 
 pub struct ServerConnectionsPlugin;
@@ -13,7 +24,20 @@ impl Plugin for ServerConnectionsPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(
             Update,
-            (handle_connection_events, handle_handshake_events)
+            (
+                handle_connection_events,
+                handle_handshake_events,
+                expire_pending_handshakes,
+                expire_disconnected_players,
+                log_network_stats,
+            )
+                .chain()
+                .run_if(in_state(ServerState::Running)),
+        )
+        .add_systems(
+            PreUpdate,
+            process_scheduled_disconnects
+                .after(ServerSet::ReceivePackets)
                 .run_if(in_state(ServerState::Running)),
         );
     }
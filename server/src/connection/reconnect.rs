@@ -0,0 +1,117 @@
+use std::{collections::HashMap, time::Duration};
+
+use avian3d::prelude::*;
+use bevy::prelude::*;
+use imm_sim_shared::{
+    ownership::OwnedByClient,
+    player::components::{AcknowledgedInput, Disconnected},
+};
+
+use crate::config::GameplayConfig;
+
+/// A secret handed to a client when it joins, which it must present to take back its avatar after
+/// losing its connection. This is never replicated.
+#[derive(Clone, Component, Copy, Debug, Eq, PartialEq)]
+pub struct SessionToken(pub u64);
+
+struct AwaitingPlayer {
+    avatar: Entity,
+    session_token: SessionToken,
+    disconnected_at: Duration,
+}
+
+/// The avatars of players who have lost their connection, keyed by display name, which are kept
+/// around for the reconnect grace period.
+#[derive(Default, Resource)]
+pub struct DisconnectedPlayers {
+    by_display_name: HashMap<String, AwaitingPlayer>,
+}
+
+impl DisconnectedPlayers {
+    /// Hold on to an avatar, freezing it in place until its player returns.
+    pub fn hold(
+        &mut self,
+        display_name: String,
+        avatar: Entity,
+        session_token: SessionToken,
+        now: Duration,
+        commands: &mut Commands,
+    ) {
+        commands
+            .entity(avatar)
+            .insert((Disconnected, RigidBody::Kinematic, LinearVelocity::ZERO));
+
+        self.by_display_name.insert(
+            display_name,
+            AwaitingPlayer {
+                avatar,
+                session_token,
+                disconnected_at: now,
+            },
+        );
+    }
+
+    /// Whether an avatar is being held for a player with the given display name.
+    pub fn is_held(&self, display_name: &str) -> bool {
+        self.by_display_name.contains_key(display_name)
+    }
+
+    /// Hand the avatar held for the given display name to a new connection, should the session
+    /// token match. The avatar is unfrozen, and given over to the new client.
+    pub fn reclaim(
+        &mut self,
+        display_name: &str,
+        session_token: u64,
+        client_id: u64,
+        commands: &mut Commands,
+    ) -> Option<Entity> {
+        let held = self.by_display_name.get(display_name)?;
+        if held.session_token.0 != session_token {
+            return None;
+        }
+
+        let AwaitingPlayer { avatar, .. } = self.by_display_name.remove(display_name)?;
+
+        commands.entity(avatar).remove::<Disconnected>().insert((
+            RigidBody::Dynamic,
+            OwnedByClient { client_id },
+            // The reconnected client numbers its inputs from the start again.
+            AcknowledgedInput::default(),
+        ));
+
+        Some(avatar)
+    }
+
+    /// Despawn the avatar held for the given display name, such that its player can no longer take
+    /// it back. Returns `false` if no avatar was being held for them.
+    pub fn release(&mut self, display_name: &str, commands: &mut Commands) -> bool {
+        let Some(held) = self.by_display_name.remove(display_name) else {
+            return false;
+        };
+
+        commands.entity(held.avatar).despawn_recursive();
+        true
+    }
+}
+
+/// Despawn the avatars of any players who have been gone for longer than the grace period.
+pub fn expire_disconnected_players(
+    time: Res<Time<Real>>,
+    gameplay: Res<GameplayConfig>,
+    mut disconnected: ResMut<DisconnectedPlayers>,
+    mut commands: Commands,
+) {
+    let now = time.elapsed();
+
+    disconnected.by_display_name.retain(|display_name, held| {
+        let gone_for = now.saturating_sub(held.disconnected_at).as_secs_f32();
+        if gone_for < gameplay.reconnect_grace_secs {
+            return true;
+        }
+
+        info!("{display_name} did not reconnect in time, so their avatar has been removed.");
+        commands.entity(held.avatar).despawn_recursive();
+
+        false
+    });
+}
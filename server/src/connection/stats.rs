@@ -0,0 +1,40 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy_renet::renet::RenetServer;
+
+use super::tracking::ConnectionTracker;
+use crate::config::LoggingConfig;
+
+/// Log the round-trip time, packet loss and bandwidth of every connected client, as often as the
+/// [`LoggingConfig`] asks.
+pub fn log_network_stats(
+    time: Res<Time<Real>>,
+    config: Res<LoggingConfig>,
+    server: Res<RenetServer>,
+    conn_tracker: Res<ConnectionTracker>,
+    mut last_logged: Local<Duration>,
+) {
+    let Ok(interval) = Duration::try_from_secs_f32(config.network_stats_interval_secs) else {
+        return;
+    };
+    if interval.is_zero() || time.elapsed().saturating_sub(*last_logged) < interval {
+        return;
+    }
+    *last_logged = time.elapsed();
+
+    for (client_id, display_name) in conn_tracker.iter() {
+        // The listening server's own player has no connection to measure.
+        let Ok(network_info) = server.network_info(client_id) else {
+            continue;
+        };
+
+        info!(
+            "{display_name} (client {client_id}): RTT {:.0} ms, {:.1}% packet loss, sent {:.1} kbps, received {:.1} kbps",
+            network_info.rtt * 1000.0,
+            network_info.packet_loss * 100.0,
+            network_info.bytes_sent_per_second * 8.0 / 1000.0,
+            network_info.bytes_received_per_second * 8.0 / 1000.0,
+        );
+    }
+}
@@ -44,4 +44,19 @@ impl ConnectionTracker {
     pub fn id_from_display_name(&self, name: &str) -> Option<u64> {
         self.display_name_to_conn_id.get(name).map(|id| *id)
     }
+
+    /// Every tracked connection ID alongside its display name, ordered by connection ID.
+    pub fn iter(&self) -> impl Iterator<Item = (u64, &str)> {
+        self.conn_id_to_display_name
+            .iter()
+            .map(|(id, display_name)| (*id, display_name.as_str()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.conn_id_to_avatar.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.conn_id_to_avatar.is_empty()
+    }
 }
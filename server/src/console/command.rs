@@ -0,0 +1,391 @@
+use std::{
+    fmt::{self, Display},
+    net::IpAddr,
+    str::FromStr,
+};
+
+use crate::ban::BanTarget;
+
+/// A command entered into the server console.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ConsoleCommand {
+    Help,
+    /// List every connected player.
+    List,
+    /// Disconnect the player with the given display name.
+    Kick {
+        display_name: String,
+    },
+    /// Ban a display name, along with the IP of the player using it should they be connected.
+    Ban {
+        display_name: String,
+        /// How long the ban lasts, or forever if not given.
+        duration_minutes: Option<u64>,
+    },
+    /// Ban an IP address.
+    BanIp {
+        ip: IpAddr,
+        duration_minutes: Option<u64>,
+    },
+    /// Lift the ban on an IP address or display name.
+    Unban {
+        target: BanTarget,
+    },
+    /// List every active ban.
+    Bans,
+    /// Show a message to every connected player.
+    Say {
+        message: String,
+    },
+    /// Change the room password, or remove it when none is given.
+    Password {
+        password: Option<String>,
+    },
+    /// Reload the gameplay and logging settings from the config file.
+    Reload,
+    /// Disconnect everyone, and start the server again with the same settings.
+    Restart,
+    /// Stop the server and exit.
+    Stop,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum ParseCommandError {
+    Empty,
+    UnknownCommand(String),
+    MissingArgument {
+        command: &'static str,
+        argument: &'static str,
+    },
+    UnexpectedArgument(&'static str),
+    InvalidArgument {
+        command: &'static str,
+        argument: &'static str,
+        value: String,
+    },
+}
+
+impl Display for ParseCommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "no command given"),
+            Self::UnknownCommand(command) => {
+                write!(f, "unknown command `{command}`, try `help`")
+            }
+            Self::MissingArgument { command, argument } => {
+                write!(f, "`{command}` expects a <{argument}>")
+            }
+            Self::UnexpectedArgument(command) => write!(f, "`{command}` takes no arguments"),
+            Self::InvalidArgument {
+                command,
+                argument,
+                value,
+            } => write!(
+                f,
+                "`{command}` expects a <{argument}>, but `{value}` is not one"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ParseCommandError {}
+
+/// The usage of every command, as shown by `help`.
+pub const USAGE: &str = "\
+help                 Show this message.
+list                 List every connected player.
+kick <name>          Disconnect a player.
+ban <name>           Ban a display name, and the IP of its player if connected.
+tempban <mins> <name>
+                     Ban a display name for some number of minutes.
+banip <ip> [mins]    Ban an IP address, forever or for some number of minutes.
+unban <name or ip>   Lift a ban.
+bans                 List every active ban.
+say <message>        Show a message to every player.
+password [password]  Change the room password, or remove it if none is given.
+reload               Reload the gameplay and logging settings from the config file.
+restart              Disconnect everyone and start the server again.
+stop                 Stop the server and exit.";
+
+impl FromStr for ConsoleCommand {
+    type Err = ParseCommandError;
+
+    /// Parse a single line of console input.
+    ///
+    /// Everything after the command's name is taken as its argument, such that display names and
+    /// messages may contain spaces.
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let line = line.trim();
+        if line.is_empty() {
+            return Err(ParseCommandError::Empty);
+        }
+
+        let (name, argument) = match line.split_once(char::is_whitespace) {
+            Some((name, argument)) => (name, Some(argument.trim())),
+            None => (line, None),
+        };
+
+        let required = |command, argument_name| {
+            argument
+                .map(str::to_owned)
+                .ok_or(ParseCommandError::MissingArgument {
+                    command,
+                    argument: argument_name,
+                })
+        };
+
+        let no_argument = |command, parsed| match argument {
+            Some(_) => Err(ParseCommandError::UnexpectedArgument(command)),
+            None => Ok(parsed),
+        };
+
+        match name.to_lowercase().as_str() {
+            "help" => no_argument("help", Self::Help),
+            "list" => no_argument("list", Self::List),
+            "kick" => Ok(Self::Kick {
+                display_name: required("kick", "name")?,
+            }),
+            "ban" => Ok(Self::Ban {
+                display_name: required("ban", "name")?,
+                duration_minutes: None,
+            }),
+            "tempban" => {
+                let argument = required("tempban", "mins")?;
+                let (minutes, display_name) = argument.split_once(char::is_whitespace).ok_or(
+                    ParseCommandError::MissingArgument {
+                        command: "tempban",
+                        argument: "name",
+                    },
+                )?;
+
+                Ok(Self::Ban {
+                    display_name: display_name.trim().to_owned(),
+                    duration_minutes: Some(parse_minutes("tempban", minutes)?),
+                })
+            }
+            "banip" => {
+                let argument = required("banip", "ip")?;
+                let (ip, minutes) = match argument.split_once(char::is_whitespace) {
+                    Some((ip, minutes)) => (ip, Some(minutes.trim())),
+                    None => (argument.as_str(), None),
+                };
+
+                Ok(Self::BanIp {
+                    ip: ip
+                        .parse::<IpAddr>()
+                        .map_err(|_| ParseCommandError::InvalidArgument {
+                            command: "banip",
+                            argument: "ip",
+                            value: ip.to_owned(),
+                        })?,
+                    duration_minutes: minutes
+                        .map(|minutes| parse_minutes("banip", minutes))
+                        .transpose()?,
+                })
+            }
+            "unban" => {
+                let argument = required("unban", "name or ip")?;
+
+                // Anything that reads as an IP address is taken to be one.
+                let target = match argument.parse() {
+                    Ok(ip) => BanTarget::Ip(ip),
+                    Err(_) => BanTarget::DisplayName(argument),
+                };
+
+                Ok(Self::Unban { target })
+            }
+            "bans" => no_argument("bans", Self::Bans),
+            "say" => Ok(Self::Say {
+                message: required("say", "message")?,
+            }),
+            "password" => Ok(Self::Password {
+                password: argument.map(str::to_owned),
+            }),
+            "reload" => no_argument("reload", Self::Reload),
+            "restart" => no_argument("restart", Self::Restart),
+            "stop" => no_argument("stop", Self::Stop),
+            _ => Err(ParseCommandError::UnknownCommand(name.to_owned())),
+        }
+    }
+}
+
+fn parse_minutes(command: &'static str, minutes: &str) -> Result<u64, ParseCommandError> {
+    minutes
+        .parse()
+        .map_err(|_| ParseCommandError::InvalidArgument {
+            command,
+            argument: "mins",
+            value: minutes.to_owned(),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+
+    use super::*;
+
+    fn parse(line: &str) -> Result<ConsoleCommand, ParseCommandError> {
+        line.parse()
+    }
+
+    #[test]
+    fn commands_take_the_rest_of_the_line() {
+        assert_eq!(
+            parse("kick Some Player"),
+            Ok(ConsoleCommand::Kick {
+                display_name: "Some Player".to_owned(),
+            })
+        );
+        assert_eq!(
+            parse("ban Some Player"),
+            Ok(ConsoleCommand::Ban {
+                display_name: "Some Player".to_owned(),
+                duration_minutes: None,
+            })
+        );
+        assert_eq!(
+            parse("  say   back in five minutes  "),
+            Ok(ConsoleCommand::Say {
+                message: "back in five minutes".to_owned(),
+            })
+        );
+    }
+
+    #[test]
+    fn tempban_takes_minutes_then_name() {
+        assert_eq!(
+            parse("tempban 30 Some Player"),
+            Ok(ConsoleCommand::Ban {
+                display_name: "Some Player".to_owned(),
+                duration_minutes: Some(30),
+            })
+        );
+        assert_eq!(
+            parse("tempban Player 30"),
+            Err(ParseCommandError::InvalidArgument {
+                command: "tempban",
+                argument: "mins",
+                value: "Player".to_owned(),
+            })
+        );
+        assert_eq!(
+            parse("tempban 30"),
+            Err(ParseCommandError::MissingArgument {
+                command: "tempban",
+                argument: "name",
+            })
+        );
+    }
+
+    #[test]
+    fn banip_with_and_without_minutes() {
+        let ip = IpAddr::V4(Ipv4Addr::new(192, 168, 0, 7));
+
+        assert_eq!(
+            parse("banip 192.168.0.7"),
+            Ok(ConsoleCommand::BanIp {
+                ip,
+                duration_minutes: None,
+            })
+        );
+        assert_eq!(
+            parse("banip 192.168.0.7 15"),
+            Ok(ConsoleCommand::BanIp {
+                ip,
+                duration_minutes: Some(15),
+            })
+        );
+        assert_eq!(
+            parse("banip somewhere"),
+            Err(ParseCommandError::InvalidArgument {
+                command: "banip",
+                argument: "ip",
+                value: "somewhere".to_owned(),
+            })
+        );
+        assert_eq!(
+            parse("banip 192.168.0.7 soon"),
+            Err(ParseCommandError::InvalidArgument {
+                command: "banip",
+                argument: "mins",
+                value: "soon".to_owned(),
+            })
+        );
+    }
+
+    #[test]
+    fn unban_tells_ips_from_names() {
+        assert_eq!(
+            parse("unban ::1"),
+            Ok(ConsoleCommand::Unban {
+                target: BanTarget::Ip("::1".parse().unwrap()),
+            })
+        );
+        assert_eq!(
+            parse("unban 10.0.0.1"),
+            Ok(ConsoleCommand::Unban {
+                target: BanTarget::Ip(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1))),
+            })
+        );
+        assert_eq!(
+            parse("unban Some Player"),
+            Ok(ConsoleCommand::Unban {
+                target: BanTarget::DisplayName("Some Player".to_owned()),
+            })
+        );
+    }
+
+    #[test]
+    fn command_names_ignore_case() {
+        assert_eq!(parse("LIST"), Ok(ConsoleCommand::List));
+        assert_eq!(parse("Stop"), Ok(ConsoleCommand::Stop));
+    }
+
+    #[test]
+    fn password_without_argument_clears_it() {
+        assert_eq!(
+            parse("password"),
+            Ok(ConsoleCommand::Password { password: None })
+        );
+        assert_eq!(
+            parse("password hunter2"),
+            Ok(ConsoleCommand::Password {
+                password: Some("hunter2".to_owned()),
+            })
+        );
+    }
+
+    #[test]
+    fn commands_without_arguments_reject_them() {
+        for command in ["help", "list", "bans", "reload", "restart", "stop"] {
+            assert_eq!(
+                parse(&format!("{command} now")),
+                Err(ParseCommandError::UnexpectedArgument(command))
+            );
+        }
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(parse("   "), Err(ParseCommandError::Empty));
+        assert_eq!(
+            parse("teleport"),
+            Err(ParseCommandError::UnknownCommand("teleport".to_owned()))
+        );
+        assert_eq!(
+            parse("kick"),
+            Err(ParseCommandError::MissingArgument {
+                command: "kick",
+                argument: "name",
+            })
+        );
+        assert_eq!(
+            parse("say"),
+            Err(ParseCommandError::MissingArgument {
+                command: "say",
+                argument: "message",
+            })
+        );
+    }
+}
@@ -0,0 +1,267 @@
+//! A command console for the standalone server, read line by line from stdin.
+
+use std::{
+    io::{self, BufRead},
+    sync::{
+        Mutex,
+        mpsc::{self, Receiver},
+    },
+    thread,
+};
+
+use bevy::prelude::*;
+use bevy_renet::netcode::NetcodeServerTransport;
+use bevy_replicon::{prelude::*, server::ServerSet};
+use imm_sim_shared::{
+    announcement::S2CAnnouncement,
+    disconnect::{DisconnectReason, S2CDisconnectNotice},
+};
+
+use self::command::{ConsoleCommand, USAGE};
+use crate::{
+    RoomAuthentication, ServerLifecycleCmd, ServerState,
+    ban::{Ban, BanList, BanTarget, unix_time_now},
+    config::ReloadConfig,
+    connection::disconnect::ScheduledDisconnects,
+    connection::reconnect::DisconnectedPlayers,
+    connection::tracking::ConnectionTracker,
+};
+
+pub mod command;
+
+/// Reads [`ConsoleCommand`]s from stdin and acts on them while the server is running.
+///
+/// Stdin is read on a separate thread, such that the app is never blocked waiting on input. Once
+/// the server has stopped, the app exits.
+pub struct ConsolePlugin;
+
+impl Plugin for ConsolePlugin {
+    fn build(&self, app: &mut App) {
+        let (sender, receiver) = mpsc::channel();
+
+        thread::Builder::new()
+            .name("console".to_owned())
+            .spawn(move || {
+                for line in io::stdin().lock().lines() {
+                    let Ok(line) = line else {
+                        break;
+                    };
+
+                    if sender.send(line).is_err() {
+                        break;
+                    }
+                }
+            })
+            .expect("Could not spawn the console thread");
+
+        app.insert_resource(ConsoleInput(Mutex::new(receiver)))
+            .add_systems(
+                Update,
+                run_console_commands.run_if(in_state(ServerState::Running)),
+            )
+            .add_systems(
+                PreUpdate,
+                // Give the transport a chance to send disconnect packets to every client first.
+                exit_once_stopped
+                    .after(ServerSet::ReceivePackets)
+                    .run_if(in_state(ServerState::Stopped)),
+            );
+    }
+}
+
+/// Lines read from stdin by the console thread.
+#[derive(Resource)]
+struct ConsoleInput(Mutex<Receiver<String>>);
+
+fn run_console_commands(
+    input: Res<ConsoleInput>,
+    conn_tracker: Res<ConnectionTracker>,
+    mut authentication: ResMut<RoomAuthentication>,
+    mut ban_list: ResMut<BanList>,
+    mut scheduled_disconnects: ResMut<ScheduledDisconnects>,
+    mut disconnected: ResMut<DisconnectedPlayers>,
+    transport: Res<NetcodeServerTransport>,
+    mut announcements: EventWriter<ToClients<S2CAnnouncement>>,
+    mut notices: EventWriter<ToClients<S2CDisconnectNotice>>,
+    mut lifecycle: EventWriter<ServerLifecycleCmd>,
+    mut reload: EventWriter<ReloadConfig>,
+    mut commands: Commands,
+) {
+    let receiver = input.0.lock().expect("Console input lock was poisoned");
+
+    // Should stdin be closed, as when running without a terminal, the channel is simply empty.
+    while let Ok(line) = receiver.try_recv() {
+        let command = match line.parse::<ConsoleCommand>() {
+            Ok(command) => command,
+            Err(e) => {
+                error!("{e}");
+                continue;
+            }
+        };
+
+        match command {
+            ConsoleCommand::Help => info!("Available commands:\n{USAGE}"),
+
+            ConsoleCommand::List => {
+                let mut list = format!("{} player(s) connected:", conn_tracker.len());
+                for (id, display_name) in conn_tracker.iter() {
+                    list.push_str(&format!("\n  {display_name} (client {id})"));
+                }
+
+                info!("{list}");
+            }
+
+            ConsoleCommand::Kick { display_name } => {
+                let Some(id) = conn_tracker.id_from_display_name(&display_name) else {
+                    error!("No player named `{display_name}` is connected.");
+                    continue;
+                };
+
+                // The avatar is despawned once the disconnection is handled.
+                scheduled_disconnects.force_disconnect(id, DisconnectReason::Kicked, &mut notices);
+                info!("Kicked {display_name}.");
+            }
+
+            ConsoleCommand::Ban {
+                display_name,
+                duration_minutes,
+            } => {
+                let expires_at = expiry_from_minutes(duration_minutes);
+                let mut targets = vec![BanTarget::DisplayName(display_name.clone())];
+
+                if let Some(id) = conn_tracker.id_from_display_name(&display_name) {
+                    if let Some(addr) = transport.client_addr(id) {
+                        targets.push(BanTarget::Ip(addr.ip()));
+                    }
+
+                    scheduled_disconnects.force_disconnect(
+                        id,
+                        DisconnectReason::Banned,
+                        &mut notices,
+                    );
+                }
+
+                // Nor may a banned player who has already left come back to their avatar.
+                if disconnected.release(&display_name, &mut commands) {
+                    info!("Removed the avatar held for {display_name}.");
+                }
+
+                for target in targets {
+                    info!("Banned {target}.");
+                    ban_list.add(Ban {
+                        target,
+                        reason: None,
+                        expires_at,
+                    });
+                }
+
+                save_ban_list(&ban_list);
+            }
+
+            ConsoleCommand::BanIp {
+                ip,
+                duration_minutes,
+            } => {
+                for (id, display_name) in conn_tracker.iter() {
+                    if transport
+                        .client_addr(id)
+                        .is_some_and(|addr| addr.ip() == ip)
+                    {
+                        info!("Disconnecting {display_name}, who is connected from {ip}.");
+                        scheduled_disconnects.force_disconnect(
+                            id,
+                            DisconnectReason::Banned,
+                            &mut notices,
+                        );
+                    }
+                }
+
+                let target = BanTarget::Ip(ip);
+                info!("Banned {target}.");
+                ban_list.add(Ban {
+                    target,
+                    reason: None,
+                    expires_at: expiry_from_minutes(duration_minutes),
+                });
+
+                save_ban_list(&ban_list);
+            }
+
+            ConsoleCommand::Unban { target } => {
+                if ban_list.remove(&target) {
+                    info!("Lifted the ban on {target}.");
+                    save_ban_list(&ban_list);
+                } else {
+                    error!("There is no ban on {target}.");
+                }
+            }
+
+            ConsoleCommand::Bans => {
+                let now = unix_time_now();
+                let mut list = "Active bans:".to_owned();
+
+                for ban in ban_list.iter() {
+                    list.push_str(&format!("\n  {}", ban.target));
+
+                    if let Some(expires_at) = ban.expires_at {
+                        let minutes = expires_at.saturating_sub(now).div_ceil(60);
+                        list.push_str(&format!(", for another {minutes} minute(s)"));
+                    }
+                    if let Some(reason) = ban.reason.as_ref() {
+                        list.push_str(&format!(": {reason}"));
+                    }
+                }
+
+                info!("{list}");
+            }
+
+            ConsoleCommand::Say { message } => {
+                info!("[Server] {message}");
+                announcements.send(ToClients {
+                    mode: SendMode::Broadcast,
+                    event: S2CAnnouncement { message },
+                });
+            }
+
+            ConsoleCommand::Password { password } => {
+                // Only affects handshakes from here on; connected players are left alone.
+                *authentication = match password {
+                    Some(password) => {
+                        info!("Changed the room password.");
+                        RoomAuthentication::WithPassword(password)
+                    }
+                    None => {
+                        info!("Removed the room password.");
+                        RoomAuthentication::WithoutPassword
+                    }
+                };
+            }
+
+            ConsoleCommand::Reload => {
+                reload.send(ReloadConfig);
+            }
+
+            ConsoleCommand::Restart => {
+                lifecycle.send(ServerLifecycleCmd::RestartServer);
+            }
+
+            ConsoleCommand::Stop => {
+                lifecycle.send(ServerLifecycleCmd::StopServer);
+            }
+        }
+    }
+}
+
+fn expiry_from_minutes(duration_minutes: Option<u64>) -> Option<u64> {
+    duration_minutes.map(|minutes| unix_time_now() + minutes * 60)
+}
+
+fn save_ban_list(ban_list: &BanList) {
+    if let Err(e) = ban_list.save() {
+        error!("Could not save the ban list: {e}");
+    }
+}
+
+fn exit_once_stopped(mut exit: EventWriter<AppExit>) {
+    exit.send(AppExit::Success);
+}
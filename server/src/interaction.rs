@@ -0,0 +1,96 @@
+use avian3d::prelude::*;
+use bevy::prelude::*;
+use bevy_replicon::prelude::*;
+use imm_sim_shared::{
+    interaction::{
+        C2SInteract, INTERACTION_RANGE_TOLERANCE, Interactable, cast_interaction_ray,
+        eye_translation,
+    },
+    physics::components::movement::Crouching,
+};
+
+use crate::{ServerState, connection::tracking::ConnectionTracker};
+
+/// Checks every [`C2SInteract`] a client sends, and passes those which hold up on as
+/// [`PlayerInteracted`] events for gameplay systems to act on.
+pub struct ServerInteractionPlugin;
+
+impl Plugin for ServerInteractionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<PlayerInteracted>().add_systems(
+            Update,
+            validate_interactions.run_if(in_state(ServerState::Running)),
+        );
+    }
+}
+
+/// A player has used an [`Interactable`], which has been checked to be within range and in sight of
+/// them.
+///
+/// Gameplay systems should read these, and act on those whose `target` they are interested in.
+#[derive(Clone, Copy, Debug, Event)]
+pub struct PlayerInteracted {
+    pub client_id: u64,
+    /// The player's avatar.
+    pub player: Entity,
+    pub target: Entity,
+}
+
+fn validate_interactions(
+    mut reader: EventReader<FromClient<C2SInteract>>,
+    mut writer: EventWriter<PlayerInteracted>,
+    conn_tracker: Res<ConnectionTracker>,
+    spatial_query: SpatialQuery,
+    collider_parents: Query<&ColliderParent>,
+    players: Query<(&Transform, Has<Crouching>)>,
+    interactables: Query<(&Interactable, &GlobalTransform)>,
+) {
+    for FromClient {
+        client_id,
+        event: C2SInteract { target },
+    } in reader.read()
+    {
+        let client_id = client_id.get();
+        let Some(player) = conn_tracker.get_avatar(client_id) else {
+            debug!("Unexpected interaction from client {client_id}. This client is not tracked.");
+            continue;
+        };
+
+        let Ok((player_transform, is_crouching)) = players.get(player) else {
+            error!("Player {client_id}'s avatar is missing a component.");
+            continue;
+        };
+
+        // The client may have looked at something which has since been despawned.
+        let Ok((interactable, target_transform)) = interactables.get(*target) else {
+            debug!("Client {client_id} tried to use {target}, which is not interactable.");
+            continue;
+        };
+
+        // The ray is cast towards the target's centre, and must reach it before anything else.
+        let eye = eye_translation(player_transform.translation, is_crouching);
+        let Ok(direction) = Dir3::new(target_transform.translation() - eye) else {
+            continue;
+        };
+        let max_distance = interactable.range + INTERACTION_RANGE_TOLERANCE;
+
+        match cast_interaction_ray(
+            &spatial_query,
+            &collider_parents,
+            eye,
+            direction,
+            max_distance,
+        ) {
+            Some((hit, _)) if hit == *target => {
+                writer.send(PlayerInteracted {
+                    client_id,
+                    player,
+                    target: *target,
+                });
+            }
+            _ => {
+                debug!("Client {client_id} tried to use {target}, which is out of range or sight.")
+            }
+        }
+    }
+}
@@ -0,0 +1,247 @@
+use avian3d::prelude::*;
+use bevy::prelude::*;
+use bevy_replicon::prelude::*;
+use imm_sim_shared::{
+    interaction::eye_translation,
+    inventory::{C2SInventoryCommand, Inventory},
+    level::{LoadedLevel, definition::ItemDefinition},
+    ownership::OwnedByClient,
+    physics::components::{
+        collision::{CoLayer, PLAYER_HEIGHT, distance_clear_of_player},
+        movement::Crouching,
+    },
+    player::components::{LookDirection, Player},
+};
+
+use crate::{ServerState, connection::tracking::ConnectionTracker, level::pickup::spawn_pickup};
+
+/// How far in front of a player's eyes dropped items appear, unless something is in the way or
+/// the item is too large to clear the player at this distance.
+const DROP_DISTANCE: f32 = 0.7;
+
+/// How far dropped items are kept from whatever is in the way of [`DROP_DISTANCE`].
+const DROP_CLEARANCE: f32 = 0.05;
+
+/// How fast dropped items are tossed away from the player, on top of the player's own velocity.
+const DROP_SPEED: f32 = 2.0;
+
+/// Gives every player an [`Inventory`], which only they are sent, and rearranges it as they ask.
+///
+/// Gameplay systems add and remove items by finding the inventory through the avatar's
+/// [`PlayerInventory`], and changing it directly.
+pub struct ServerInventoryPlugin;
+
+impl Plugin for ServerInventoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                give_players_inventories,
+                follow_avatar_owners,
+                update_inventory_visibility,
+                despawn_orphaned_inventories,
+                handle_inventory_commands,
+            )
+                .chain()
+                .run_if(in_state(ServerState::Running)),
+        );
+    }
+}
+
+/// The entity holding this player's [`Inventory`].
+#[derive(Clone, Component, Copy, Debug)]
+pub struct PlayerInventory(pub Entity);
+
+/// The avatar whose [`Inventory`] this is.
+#[derive(Clone, Component, Copy, Debug)]
+struct InventoryOf(Entity);
+
+fn give_players_inventories(
+    avatars: Query<(Entity, &OwnedByClient), (With<Player>, Without<PlayerInventory>)>,
+    mut commands: Commands,
+) {
+    for (avatar, owner) in avatars.iter() {
+        let inventory = commands
+            .spawn((
+                Replicated,
+                Inventory::default(),
+                *owner,
+                InventoryOf(avatar),
+            ))
+            .id();
+        commands.entity(avatar).insert(PlayerInventory(inventory));
+    }
+}
+
+/// Hand an inventory over along with its avatar, such as when a player reconnects under a new
+/// client ID.
+fn follow_avatar_owners(
+    avatars: Query<
+        (&OwnedByClient, &PlayerInventory),
+        (Changed<OwnedByClient>, Without<Inventory>),
+    >,
+    mut inventories: Query<&mut OwnedByClient, With<Inventory>>,
+) {
+    for (owner, inventory) in avatars.iter() {
+        if let Ok(mut inventory_owner) = inventories.get_mut(inventory.0) {
+            inventory_owner.set_if_neq(*owner);
+        }
+    }
+}
+
+/// Show each inventory to its owner alone, whenever it changes hands or a client connects.
+fn update_inventory_visibility(
+    mut server_events: EventReader<ServerEvent>,
+    mut replicated_clients: ResMut<ReplicatedClients>,
+    inventories: Query<(Entity, &OwnedByClient), With<Inventory>>,
+    changed: Query<(), (With<Inventory>, Changed<OwnedByClient>)>,
+) {
+    let connected = server_events
+        .read()
+        .filter_map(|event| match event {
+            ServerEvent::ClientConnected { client_id } => Some(*client_id),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    for client in replicated_clients.iter_mut() {
+        let is_new = connected.contains(&client.id());
+        let client_id = client.id().get();
+
+        for (entity, owner) in inventories.iter() {
+            if is_new || changed.contains(entity) {
+                client
+                    .visibility_mut()
+                    .set_visibility(entity, owner.client_id == client_id);
+            }
+        }
+    }
+}
+
+fn despawn_orphaned_inventories(
+    inventories: Query<(Entity, &InventoryOf)>,
+    avatars: Query<(), With<PlayerInventory>>,
+    mut commands: Commands,
+) {
+    for (entity, InventoryOf(avatar)) in inventories.iter() {
+        if !avatars.contains(*avatar) {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn handle_inventory_commands(
+    mut reader: EventReader<FromClient<C2SInventoryCommand>>,
+    conn_tracker: Res<ConnectionTracker>,
+    level: Res<LoadedLevel>,
+    spatial_query: SpatialQuery,
+    avatars: Query<(
+        &PlayerInventory,
+        &Transform,
+        &LookDirection,
+        &LinearVelocity,
+        Has<Crouching>,
+    )>,
+    mut inventories: Query<&mut Inventory>,
+    mut commands: Commands,
+) {
+    for FromClient { client_id, event } in reader.read() {
+        let client_id = client_id.get();
+        let Some((player_inventory, transform, look, lin_vel, is_crouching)) = conn_tracker
+            .get_avatar(client_id)
+            .and_then(|avatar| avatars.get(avatar).ok())
+        else {
+            debug!("Unexpected inventory command from client {client_id}, who has no avatar.");
+            continue;
+        };
+        let Ok(mut inventory) = inventories.get_mut(player_inventory.0) else {
+            error!("Player {client_id}'s inventory is missing.");
+            continue;
+        };
+
+        let result = match *event {
+            C2SInventoryCommand::Move { from, to } => inventory.move_stack(from, to),
+            C2SInventoryCommand::Drop { slot } => {
+                let Some(definition) = inventory.slots().get(slot).and_then(|stack| {
+                    let item = &stack.as_ref()?.item;
+
+                    Some(
+                        level
+                            .0
+                            .item_definition_for(item)
+                            .cloned()
+                            .unwrap_or_else(|| ItemDefinition::fallback(item.clone())),
+                    )
+                }) else {
+                    debug!("Client {client_id} has nothing in slot {slot} to drop.");
+                    continue;
+                };
+
+                let Some((translation, direction)) = drop_placement(
+                    &spatial_query,
+                    transform.translation,
+                    is_crouching,
+                    look,
+                    definition.extents,
+                ) else {
+                    debug!("Client {client_id} has no room to drop anything.");
+                    continue;
+                };
+
+                inventory.remove(slot, u32::MAX).map(|stack| {
+                    spawn_pickup(
+                        stack,
+                        &definition,
+                        Transform::from_translation(translation)
+                            .with_rotation(look.body_rotation()),
+                        lin_vel.0 + direction * DROP_SPEED,
+                        &mut commands,
+                    );
+                })
+            }
+        };
+
+        // The client's view of its inventory may simply be out of date.
+        if let Err(e) = result {
+            debug!("Client {client_id} sent an inventory command that can't be carried out: {e}");
+        }
+    }
+}
+
+/// Where to drop an item of the given extents, and the direction to toss it in: in front of the
+/// player's eyes, clear of their own collider and short of any wall they are facing. Returns `None`
+/// should there be no room.
+fn drop_placement(
+    spatial_query: &SpatialQuery,
+    translation: Vec3,
+    is_crouching: bool,
+    look: &LookDirection,
+    extents: Vec3,
+) -> Option<(Vec3, Dir3)> {
+    let eye = eye_translation(translation, is_crouching);
+    let rotation = look.body_rotation();
+    let shape = Collider::cuboid(extents.x, extents.y, extents.z);
+    let filter = SpatialQueryFilter::from_mask(CoLayer::Environment);
+
+    // A player looking down at their feet has their own body in the way, so try straight ahead too.
+    [look.direction(), rotation * Dir3::NEG_Z]
+        .into_iter()
+        .find_map(|direction| {
+            let clear = distance_clear_of_player(
+                PLAYER_HEIGHT,
+                translation,
+                is_crouching,
+                eye,
+                direction,
+                extents.length() / 2.0,
+            );
+            // Larger items are held further out, such that they still clear the player.
+            let max_distance = DROP_DISTANCE.max(clear);
+            let config = ShapeCastConfig::from_max_distance(max_distance);
+            let distance = spatial_query
+                .cast_shape(&shape, eye, rotation, direction, &config, &filter)
+                .map_or(max_distance, |hit| hit.distance - DROP_CLEARANCE);
+
+            (distance >= clear).then(|| (eye + direction * distance, direction))
+        })
+}
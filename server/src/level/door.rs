@@ -0,0 +1,274 @@
+use avian3d::prelude::*;
+use bevy::prelude::*;
+use bevy_replicon::prelude::*;
+use imm_sim_shared::{
+    interaction::Interactable,
+    inventory::{Inventory, Item},
+    level::{
+        Door,
+        definition::{
+            DoorDefinition, DoorMotion, DoorState, color_from_srgb, rotation_from_degrees,
+        },
+    },
+    physics::components::transform::ReplicatedTransform,
+};
+
+use crate::{ServerState, interaction::PlayerInteracted, inventory::PlayerInventory};
+
+/// How far, in metres, from a door a player may open or close it.
+const DOOR_USE_RANGE: f32 = 2.5;
+
+/// Opens, closes, locks and jams [`Door`]s on [`DoorCommand`]s, and moves their colliders to
+/// match.
+///
+/// Using a door toggles it, first unlocking it should the player carry its [`Item::Key`]. Anything
+/// else, such as scripts, can drive doors by sending [`DoorCommand`]s, finding them by their
+/// [`DoorId`].
+pub struct DoorPlugin;
+
+impl Plugin for DoorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<DoorCommand>()
+            .add_systems(
+                Update,
+                (use_doors, apply_door_commands)
+                    .chain()
+                    .run_if(in_state(ServerState::Running)),
+            )
+            .add_systems(
+                FixedUpdate,
+                move_doors.run_if(in_state(ServerState::Running)),
+            );
+    }
+}
+
+/// The name a door was given in the level, for keys and scripts to find it by.
+#[derive(Clone, Component, Debug, Eq, PartialEq)]
+pub struct DoorId(pub String);
+
+/// Asks for a door to change its state. Should the door's current state not allow it, such as
+/// opening a locked door, nothing happens.
+#[derive(Clone, Copy, Debug, Event)]
+pub struct DoorCommand {
+    pub door: Entity,
+    pub action: DoorAction,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DoorAction {
+    Open,
+    Close,
+    /// Open the door if closed, or close it if open.
+    Toggle,
+    /// Lock the door, should it be closed.
+    Lock,
+    Unlock,
+    /// Stop the door wherever it is.
+    Jam,
+    /// Free a jammed door, leaving it open or closed depending on how far open it is.
+    Unjam,
+}
+
+/// How a door moves and how far open it is.
+#[derive(Component)]
+struct DoorMechanism {
+    closed: Transform,
+    motion: DoorMotion,
+    open_secs: f32,
+    /// From 0.0 when fully closed to 1.0 when fully open.
+    openness: f32,
+}
+
+impl DoorMechanism {
+    /// Where the door is when opened by the given amount.
+    fn transform_at(&self, openness: f32) -> Transform {
+        match self.motion {
+            DoorMotion::Hinge { angle } => {
+                let swing = Quat::from_rotation_y((angle * openness).to_radians());
+                let hinge = Vec3::new(-self.closed.scale.x * 0.5, 0.0, 0.0);
+
+                // Rotate about the hinge, rather than about the door's centre.
+                let hinge_world = self.closed.translation + self.closed.rotation * hinge;
+                Transform {
+                    translation: hinge_world - self.closed.rotation * swing * hinge,
+                    rotation: self.closed.rotation * swing,
+                    scale: Vec3::ONE,
+                }
+            }
+            DoorMotion::Slide { offset } => Transform {
+                translation: self.closed.translation + self.closed.rotation * (offset * openness),
+                rotation: self.closed.rotation,
+                scale: Vec3::ONE,
+            },
+        }
+    }
+}
+
+/// Spawn a door from its level definition.
+pub fn spawn_door(door: &DoorDefinition, commands: &mut Commands) {
+    let transform = Transform::from_translation(door.translation)
+        .with_rotation(rotation_from_degrees(door.rotation));
+    let Vec3 { x, y, z } = door.extents;
+
+    let mut cmd = commands.spawn((
+        Replicated,
+        Door {
+            extents: door.extents,
+            color: color_from_srgb(door.color),
+            state: door.state,
+        },
+        Interactable {
+            prompt: door_prompt(door.state).to_owned(),
+            range: DOOR_USE_RANGE,
+        },
+        ReplicatedTransform::from(transform),
+        transform,
+        DoorMechanism {
+            // The extents are kept in the scale, such that the hinge can be found.
+            closed: transform.with_scale(door.extents),
+            motion: door.motion,
+            open_secs: door.open_secs,
+            openness: if door.state == DoorState::Open {
+                1.0
+            } else {
+                0.0
+            },
+        },
+        RigidBody::Kinematic,
+        Collider::cuboid(x, y, z),
+    ));
+
+    if let Some(id) = door.id.as_ref() {
+        cmd.insert(DoorId(id.clone()));
+    }
+}
+
+fn door_prompt(state: DoorState) -> &'static str {
+    match state {
+        DoorState::Closed => "Open door",
+        DoorState::Open => "Close door",
+        DoorState::Locked => "Locked",
+        DoorState::Jammed => "Jammed",
+    }
+}
+
+/// The state a door moves to on the given action, or `None` should it not be allowed.
+fn next_state(state: DoorState, action: DoorAction, openness: f32) -> Option<DoorState> {
+    use DoorAction as A;
+    use DoorState as S;
+
+    match (state, action) {
+        (S::Closed, A::Open | A::Toggle) => Some(S::Open),
+        (S::Open, A::Close | A::Toggle) => Some(S::Closed),
+        (S::Closed, A::Lock) => Some(S::Locked),
+        (S::Locked, A::Unlock) => Some(S::Closed),
+        (S::Closed | S::Open | S::Locked, A::Jam) => Some(S::Jammed),
+        (S::Jammed, A::Unjam) if openness >= 0.5 => Some(S::Open),
+        (S::Jammed, A::Unjam) => Some(S::Closed),
+        _ => None,
+    }
+}
+
+fn use_doors(
+    mut reader: EventReader<PlayerInteracted>,
+    mut writer: EventWriter<DoorCommand>,
+    doors: Query<(&Door, Option<&DoorId>)>,
+    avatars: Query<&PlayerInventory>,
+    inventories: Query<&Inventory>,
+) {
+    for PlayerInteracted { player, target, .. } in reader.read() {
+        let Ok((door, id)) = doors.get(*target) else {
+            continue;
+        };
+
+        let has_key = |DoorId(door_id): &DoorId| {
+            let key = Item::Key {
+                door_id: door_id.clone(),
+            };
+            avatars
+                .get(*player)
+                .ok()
+                .and_then(|inventory| inventories.get(inventory.0).ok())
+                .is_some_and(|inventory| inventory.contains(&key))
+        };
+
+        if door.state == DoorState::Locked && id.is_some_and(has_key) {
+            writer.send(DoorCommand {
+                door: *target,
+                action: DoorAction::Unlock,
+            });
+        }
+
+        writer.send(DoorCommand {
+            door: *target,
+            action: DoorAction::Toggle,
+        });
+    }
+}
+
+fn apply_door_commands(
+    mut reader: EventReader<DoorCommand>,
+    mut doors: Query<(&mut Door, &mut Interactable, &DoorMechanism)>,
+) {
+    for DoorCommand {
+        door: entity,
+        action,
+    } in reader.read()
+    {
+        let Ok((mut door, mut interactable, mechanism)) = doors.get_mut(*entity) else {
+            warn!("A door command was sent to {entity}, which is not a door.");
+            continue;
+        };
+
+        let Some(state) = next_state(door.state, *action, mechanism.openness) else {
+            debug!("A {:?} door can't be sent {action:?}.", door.state);
+            continue;
+        };
+
+        door.state = state;
+        interactable.prompt = door_prompt(state).to_owned();
+    }
+}
+
+/// Move every door towards being open or closed, as its state asks.
+///
+/// Doors are moved by their velocity rather than being teleported, such that they push whatever is
+/// in their way.
+fn move_doors(
+    time: Res<Time>,
+    mut doors: Query<(
+        &Door,
+        &mut DoorMechanism,
+        &Position,
+        &Rotation,
+        &mut LinearVelocity,
+        &mut AngularVelocity,
+    )>,
+) {
+    let delta_secs = time.delta_secs();
+    if delta_secs <= 0.0 {
+        return;
+    }
+
+    for (door, mut mechanism, position, rotation, mut lin_vel, mut ang_vel) in doors.iter_mut() {
+        let target_openness = match door.state {
+            DoorState::Open => 1.0,
+            DoorState::Closed | DoorState::Locked => 0.0,
+            DoorState::Jammed => mechanism.openness,
+        };
+
+        let max_step = delta_secs / mechanism.open_secs;
+        mechanism.openness += (target_openness - mechanism.openness).clamp(-max_step, max_step);
+
+        let target = mechanism.transform_at(mechanism.openness);
+
+        lin_vel.0 = (target.translation - position.0) / delta_secs;
+
+        // Take the shorter way around, should the rotations lie in opposite hemispheres.
+        let mut delta = target.rotation * rotation.0.inverse();
+        if delta.w < 0.0 {
+            delta = -delta;
+        }
+        ang_vel.0 = delta.to_scaled_axis() / delta_secs;
+    }
+}
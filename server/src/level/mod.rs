@@ -0,0 +1,82 @@
+use avian3d::prelude::*;
+use bevy::prelude::*;
+use bevy_replicon::prelude::*;
+use imm_sim_shared::{
+    inventory::ItemStack,
+    level::{
+        LevelPlugin, LoadedLevel, Prop,
+        definition::{EntityDefinition, color_from_srgb, rotation_from_degrees},
+    },
+    physics::components::transform::ReplicatedTransform,
+};
+
+use self::{
+    door::{DoorPlugin, spawn_door},
+    pickup::{PickupPlugin, spawn_pickup},
+};
+use crate::{ServerState, carry::carryable};
+
+pub mod door;
+pub mod pickup;
+
+/// Loads the level through the shared [`LevelPlugin`], and spawns its gameplay entities whenever the
+/// server starts.
+pub struct ServerLevelPlugin;
+
+impl Plugin for ServerLevelPlugin {
+    fn build(&self, app: &mut App) {
+        // The client may have already added this when the server runs alongside it.
+        if !app.is_plugin_added::<LevelPlugin>() {
+            app.add_plugins(LevelPlugin);
+        }
+
+        app.add_plugins((DoorPlugin, PickupPlugin))
+            .add_systems(OnEnter(ServerState::Running), spawn_level_entities);
+    }
+}
+
+fn spawn_level_entities(level: Res<LoadedLevel>, mut commands: Commands) {
+    for entity in level.0.entities.iter() {
+        match entity {
+            EntityDefinition::Prop(prop) => {
+                let transform = Transform::from_translation(prop.translation)
+                    .with_rotation(rotation_from_degrees(prop.rotation));
+                let Vec3 { x, y, z } = prop.extents;
+
+                commands.spawn((
+                    Replicated,
+                    Prop {
+                        extents: prop.extents,
+                        color: color_from_srgb(prop.color),
+                    },
+                    ReplicatedTransform::from(transform),
+                    transform,
+                    RigidBody::Dynamic,
+                    Collider::cuboid(x, y, z),
+                    Mass(prop.mass),
+                    carryable(prop.mass),
+                ));
+            }
+            EntityDefinition::Door(door) => spawn_door(door, &mut commands),
+            EntityDefinition::Pickup(pickup) => {
+                // Levels are validated on loading, such that every pickup's item is defined.
+                let Some(definition) = level.0.item_definition(&pickup.item) else {
+                    error!(
+                        "Pickup refers to item `{}`, which is not defined.",
+                        pickup.item
+                    );
+                    continue;
+                };
+
+                let stack = ItemStack {
+                    item: definition.item.clone(),
+                    count: pickup.count,
+                };
+                let transform = Transform::from_translation(pickup.translation)
+                    .with_rotation(rotation_from_degrees(pickup.rotation));
+
+                spawn_pickup(stack, definition, transform, Vec3::ZERO, &mut commands);
+            }
+        }
+    }
+}
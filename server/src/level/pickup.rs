@@ -0,0 +1,113 @@
+use avian3d::prelude::*;
+use bevy::prelude::*;
+use bevy_replicon::prelude::*;
+use imm_sim_shared::{
+    interaction::Interactable,
+    inventory::{Inventory, ItemStack},
+    level::{
+        Pickup,
+        definition::{ItemDefinition, color_from_srgb},
+    },
+    physics::components::{collision::pickup_collision_layers, transform::ReplicatedTransform},
+};
+
+use crate::{ServerState, interaction::PlayerInteracted, inventory::PlayerInventory};
+
+/// How far, in metres, from a pickup a player may pick it up.
+const PICKUP_RANGE: f32 = 2.0;
+
+/// Moves the items in [`Pickup`]s into the inventory of whoever uses them.
+///
+/// Should a player not have room for all of a stack, they take what fits and leave the rest.
+pub struct PickupPlugin;
+
+impl Plugin for PickupPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, pick_up_items.run_if(in_state(ServerState::Running)));
+    }
+}
+
+/// Spawn a stack of items lying in the world, looking as the given definition describes.
+pub fn spawn_pickup(
+    stack: ItemStack,
+    definition: &ItemDefinition,
+    transform: Transform,
+    velocity: Vec3,
+    commands: &mut Commands,
+) {
+    let Vec3 { x, y, z } = definition.extents;
+
+    commands.spawn((
+        Replicated,
+        Interactable {
+            prompt: pickup_prompt(&stack),
+            range: PICKUP_RANGE,
+        },
+        Pickup {
+            stack,
+            extents: definition.extents,
+            color: color_from_srgb(definition.color),
+        },
+        ReplicatedTransform::from(transform),
+        transform,
+        RigidBody::Dynamic,
+        Collider::cuboid(x, y, z),
+        pickup_collision_layers(),
+        Mass(definition.mass),
+        LinearVelocity(velocity),
+    ));
+}
+
+fn pickup_prompt(stack: &ItemStack) -> String {
+    if stack.count > 1 {
+        format!("Pick up {} (×{})", stack.item, stack.count)
+    } else {
+        format!("Pick up {}", stack.item)
+    }
+}
+
+fn pick_up_items(
+    mut reader: EventReader<PlayerInteracted>,
+    avatars: Query<&PlayerInventory>,
+    mut inventories: Query<&mut Inventory>,
+    mut pickups: Query<(&mut Pickup, &mut Interactable)>,
+    mut commands: Commands,
+) {
+    for PlayerInteracted {
+        client_id,
+        player,
+        target,
+    } in reader.read()
+    {
+        let Ok((mut pickup, mut interactable)) = pickups.get_mut(*target) else {
+            continue;
+        };
+
+        // Two players may have used the same pickup at once, and the first took all of it.
+        if pickup.stack.count == 0 {
+            continue;
+        }
+
+        let Some(mut inventory) = avatars
+            .get(*player)
+            .ok()
+            .and_then(|inventory| inventories.get_mut(inventory.0).ok())
+        else {
+            error!("Player {client_id}'s avatar has no inventory.");
+            continue;
+        };
+
+        let left_over = inventory.add(pickup.stack.item.clone(), pickup.stack.count);
+        if left_over == pickup.stack.count {
+            debug!("Player {client_id} has no room for {}.", pickup.stack.item);
+            continue;
+        }
+
+        pickup.stack.count = left_over;
+        if left_over == 0 {
+            commands.entity(*target).despawn_recursive();
+        } else {
+            interactable.prompt = pickup_prompt(&pickup.stack);
+        }
+    }
+}
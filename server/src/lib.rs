@@ -1,13 +1,14 @@
 use std::{
     net::{SocketAddr, UdpSocket},
-    time::SystemTime,
+    path::PathBuf,
+    time::{Duration, SystemTime},
 };
 
 use avian3d::PhysicsPlugins;
 use bevy::{
-    app::{PanicHandlerPlugin, TerminalCtrlCHandlerPlugin},
+    app::{PanicHandlerPlugin, PluginGroupBuilder, TerminalCtrlCHandlerPlugin},
     diagnostic::DiagnosticsPlugin,
-    log::LogPlugin,
+    log::{Level, LogPlugin},
     prelude::*,
     scene::ScenePlugin,
     state::app::StatesPlugin,
@@ -18,17 +19,39 @@ use bevy_renet::{
 };
 use bevy_replicon::prelude::*;
 use bevy_replicon_renet::{RenetChannelsExt, RepliconRenetPlugins};
-use imm_sim_shared::{PROTOCOL_ID_V0_1, ProtocolPlugin};
+use imm_sim_shared::{
+    FIXED_TIMESTEP_HZ, PROTOCOL_ID_V0_1, ProtocolPlugin,
+    disconnect::{DisconnectReason, S2CDisconnectNotice},
+};
 
 use self::{
+    auth::PrivateKey,
+    ban::BanList,
+    carry::ServerCarryPlugin,
+    chat::ServerChatPlugin,
+    config::ServerConfigPlugin,
     connection::{
-        ServerConnectionsPlugin, handle_incoming::AwaitingHandshakes, tracking::ConnectionTracker,
+        ServerConnectionsPlugin, disconnect::ScheduledDisconnects,
+        handle_incoming::AwaitingHandshakes, reconnect::DisconnectedPlayers,
+        tracking::ConnectionTracker,
     },
+    interaction::ServerInteractionPlugin,
+    inventory::ServerInventoryPlugin,
+    level::ServerLevelPlugin,
     physics::ServerPhysicsPlugin,
     player::ServerPlayerPlugin,
 };
 
+pub mod auth;
+pub mod ban;
+mod carry;
+mod chat;
+pub mod config;
 mod connection;
+pub mod console;
+mod interaction;
+mod inventory;
+mod level;
 mod physics;
 mod player;
 
@@ -70,7 +93,13 @@ impl Plugin for ImmSimServerPlugin {
             app.add_plugins((
                 MinimalPlugins,
                 PanicHandlerPlugin,
-                LogPlugin::default(),
+                LogPlugin {
+                    // Everything is let through here, and filtered by the [`LoggingConfig`] instead
+                    // such that it can be changed while the server runs.
+                    level: Level::TRACE,
+                    custom_layer: config::reloadable_log_filter,
+                    ..default()
+                },
                 TransformPlugin,
                 HierarchyPlugin,
                 DiagnosticsPlugin,
@@ -80,13 +109,13 @@ impl Plugin for ImmSimServerPlugin {
                 StatesPlugin,
             ))
             // Networking plugins
-            .add_plugins((RepliconPlugins, RepliconRenetPlugins))
+            .add_plugins((replicon_plugins(), RepliconRenetPlugins))
             // Custom protocol plugin
             .add_plugins(ProtocolPlugin)
             // Physics plugin
             .add_plugins(PhysicsPlugins::default())
             // Netcode and physics should be on a fixed time-step
-            .insert_resource(Time::<Fixed>::from_hz(30.0));
+            .insert_resource(Time::<Fixed>::from_hz(FIXED_TIMESTEP_HZ));
         }
 
         // Server lifecycle functionality.
@@ -94,7 +123,17 @@ impl Plugin for ImmSimServerPlugin {
             .init_state::<ServerState>()
             .add_systems(Update, listen_lifecycle_cmd)
             .add_systems(OnEnter(ServerState::Running), start_server)
-            .add_systems(OnEnter(ServerState::Stopped), stop_server);
+            .add_systems(OnEnter(ServerState::Stopping), begin_stopping)
+            .add_systems(
+                Update,
+                finish_stopping.run_if(in_state(ServerState::Stopping)),
+            );
+
+        // Settings, which may be reloaded while running
+        app.add_plugins(ServerConfigPlugin);
+
+        // Level geometry and gameplay entities
+        app.add_plugins(ServerLevelPlugin);
 
         // Handle connections and handshakes
         app.add_plugins(ServerConnectionsPlugin);
@@ -102,25 +141,79 @@ impl Plugin for ImmSimServerPlugin {
         // Handle player inputs and commands
         app.add_plugins(ServerPlayerPlugin);
 
+        // Chat between players
+        app.add_plugins(ServerChatPlugin);
+
+        // Players using things in the world
+        app.add_plugins(ServerInteractionPlugin);
+
+        // Each player's inventory of items
+        app.add_plugins(ServerInventoryPlugin);
+
+        // Picking up and throwing physics objects
+        app.add_plugins(ServerCarryPlugin);
+
         // State sync
         app.add_plugins(ServerPhysicsPlugin);
     }
 }
 
+/// Replicon's plugins, set up as the server needs them. Anything hosting a server, standalone or
+/// within a client, should add these in place of [`RepliconPlugins`].
+pub fn replicon_plugins() -> PluginGroupBuilder {
+    RepliconPlugins.build().set(ServerPlugin {
+        // Some entities, such as inventories, are hidden from all but the client they belong to.
+        visibility_policy: VisibilityPolicy::Blacklist,
+        ..default()
+    })
+}
+
 #[derive(Event)]
 pub enum ServerLifecycleCmd {
     StartServer {
         bind_addr: SocketAddr,
         room_password: Option<String>,
+        /// When given, the server runs in secure mode and only accepts clients presenting a connect
+        /// token signed with this key. See [`auth`].
+        private_key: Option<PrivateKey>,
+        /// When given, bans are loaded from and saved to this file. Otherwise they only last for as
+        /// long as the server runs. See [`ban`].
+        ban_file: Option<PathBuf>,
+        max_clients: usize,
+        handshake_limits: HandshakeLimits,
     },
+    /// Tell every client the server is stopping, disconnect them, and tear the server down such
+    /// that it can be started afresh.
     StopServer,
+    /// Stop the server, then start it again with the same settings.
+    RestartServer,
+}
+
+/// Limits on clients which have connected, but are yet to complete the handshake.
+#[derive(Clone, Copy, Debug, Resource)]
+pub struct HandshakeLimits {
+    /// How long a client has to complete the handshake before it is disconnected.
+    pub timeout: Duration,
+    /// The most clients that may be awaiting a handshake at once. Any more are turned away.
+    pub max_pending: usize,
+}
+
+impl Default for HandshakeLimits {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(10),
+            max_pending: 8,
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq, States)]
-enum ServerState {
+pub enum ServerState {
     #[default]
     NotRunning,
     Running,
+    /// Clients have been told the server is stopping, and are about to be disconnected.
+    Stopping,
     Stopped,
     Errored,
 }
@@ -134,8 +227,35 @@ enum RoomAuthentication {
     WithoutPassword,
 }
 
+#[derive(Resource)]
+struct MaxClients(usize);
+
+/// How long clients are given to receive their [`S2CDisconnectNotice`] before being disconnected.
+const DISCONNECT_NOTICE_GRACE: Duration = Duration::from_millis(250);
+
+/// Tracks the progress of the server stopping.
+#[derive(Resource)]
+struct Shutdown {
+    timer: Timer,
+    clients_disconnected: bool,
+    /// Whether to start the server again once it has stopped.
+    restart: bool,
+}
+
+/// Where the [`BanList`] is loaded from when the server starts.
+#[derive(Resource)]
+struct BanFile(Option<PathBuf>);
+
+/// Whether netcode connections must present a connect token signed with the server's private key.
+#[derive(Resource)]
+enum NetcodeSecurity {
+    Secure(PrivateKey),
+    Unsecure,
+}
+
 fn listen_lifecycle_cmd(
     mut reader: EventReader<ServerLifecycleCmd>,
+    state: Res<State<ServerState>>,
     mut next: ResMut<NextState<ServerState>>,
 
     mut commands: Commands,
@@ -145,7 +265,16 @@ fn listen_lifecycle_cmd(
             ServerLifecycleCmd::StartServer {
                 bind_addr,
                 room_password,
+                private_key,
+                ban_file,
+                max_clients,
+                handshake_limits,
             } => {
+                if matches!(state.get(), ServerState::Running | ServerState::Stopping) {
+                    warn!("Ignoring a request to start the server, as it is already running.");
+                    continue;
+                }
+
                 commands.insert_resource(BindAddr(*bind_addr));
 
                 let auth = if let Some(pass) = room_password {
@@ -155,11 +284,31 @@ fn listen_lifecycle_cmd(
                 };
                 commands.insert_resource(auth);
 
+                let security = if let Some(key) = private_key {
+                    NetcodeSecurity::Secure(*key)
+                } else {
+                    NetcodeSecurity::Unsecure
+                };
+                commands.insert_resource(security);
+                commands.insert_resource(BanFile(ban_file.clone()));
+                commands.insert_resource(MaxClients(*max_clients));
+                commands.insert_resource(*handshake_limits);
+
                 next.set(ServerState::Running);
             }
 
-            ServerLifecycleCmd::StopServer => {
-                next.set(ServerState::Stopped);
+            ServerLifecycleCmd::StopServer | ServerLifecycleCmd::RestartServer => {
+                if *state.get() != ServerState::Running {
+                    warn!("Ignoring a request to stop the server, as it is not running.");
+                    continue;
+                }
+
+                commands.insert_resource(Shutdown {
+                    timer: Timer::new(DISCONNECT_NOTICE_GRACE, TimerMode::Once),
+                    clients_disconnected: false,
+                    restart: matches!(event, ServerLifecycleCmd::RestartServer),
+                });
+                next.set(ServerState::Stopping);
             }
         }
     }
@@ -167,10 +316,23 @@ fn listen_lifecycle_cmd(
 
 fn start_server(
     bind_addr: Res<BindAddr>,
+    security: Res<NetcodeSecurity>,
+    ban_file: Res<BanFile>,
+    max_clients: Res<MaxClients>,
     channels: Res<RepliconChannels>,
     mut next: ResMut<NextState<ServerState>>,
     mut commands: Commands,
 ) {
+    let ban_list = match ban_file.0.as_deref().map(BanList::load) {
+        Some(Ok(ban_list)) => ban_list,
+        Some(Err(e)) => {
+            error!("Error loading the ban list: {e}");
+            next.set(ServerState::Errored);
+            return;
+        }
+        None => BanList::default(),
+    };
+
     let mut err_closure = |e| {
         error!("Error starting server: {e}");
         next.set(ServerState::Errored);
@@ -178,6 +340,9 @@ fn start_server(
 
     commands.init_resource::<AwaitingHandshakes>();
     commands.init_resource::<ConnectionTracker>();
+    commands.init_resource::<ScheduledDisconnects>();
+    commands.init_resource::<DisconnectedPlayers>();
+    commands.insert_resource(ban_list);
 
     let server_channels_config = channels.get_server_configs();
     let client_channels_config = channels.get_client_configs();
@@ -200,13 +365,22 @@ fn start_server(
         .duration_since(SystemTime::UNIX_EPOCH)
         .expect("System time is less than Unix epoch");
 
+    let authentication = match security.as_ref() {
+        NetcodeSecurity::Secure(private_key) => {
+            info!("Starting server in secure mode. Clients will need a connect token to join.");
+            ServerAuthentication::Secure {
+                private_key: *private_key,
+            }
+        }
+        NetcodeSecurity::Unsecure => ServerAuthentication::Unsecure,
+    };
+
     let server_config = ServerConfig {
         current_time,
-        max_clients: 32,
+        max_clients: max_clients.0,
         protocol_id: PROTOCOL_ID_V0_1,
         public_addresses: vec![bind_addr.0],
-        // FIXME: Make secure in the final version.
-        authentication: ServerAuthentication::Unsecure,
+        authentication,
     };
 
     let transport = match NetcodeServerTransport::new(server_config, socket) {
@@ -223,6 +397,55 @@ fn start_server(
     next.set(ServerState::Running);
 }
 
-fn stop_server(mut server: ResMut<RenetServer>) {
-    server.disconnect_all();
+fn begin_stopping(mut writer: EventWriter<ToClients<S2CDisconnectNotice>>) {
+    info!("Stopping the server.");
+
+    writer.send(ToClients {
+        mode: SendMode::Broadcast,
+        event: S2CDisconnectNotice {
+            reason: DisconnectReason::ServerStopping,
+        },
+    });
+}
+
+/// Once clients have had a chance to receive their [`S2CDisconnectNotice`], disconnect them. Then,
+/// once the transport has sent them their disconnect packets, tear everything down.
+fn finish_stopping(
+    time: Res<Time>,
+    mut shutdown: ResMut<Shutdown>,
+    mut server: ResMut<RenetServer>,
+    mut next: ResMut<NextState<ServerState>>,
+    replicated: Query<Entity, With<Replicated>>,
+    mut commands: Commands,
+) {
+    if !shutdown.timer.tick(time.delta()).finished() {
+        return;
+    }
+
+    if !shutdown.clients_disconnected {
+        server.disconnect_all();
+        shutdown.clients_disconnected = true;
+        return;
+    }
+
+    for entity in replicated.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    commands.remove_resource::<RenetServer>();
+    commands.remove_resource::<NetcodeServerTransport>();
+    commands.remove_resource::<AwaitingHandshakes>();
+    commands.remove_resource::<ConnectionTracker>();
+    commands.remove_resource::<ScheduledDisconnects>();
+    commands.remove_resource::<DisconnectedPlayers>();
+    commands.remove_resource::<BanList>();
+    commands.remove_resource::<Shutdown>();
+
+    if shutdown.restart {
+        info!("Restarting the server.");
+        next.set(ServerState::Running);
+    } else {
+        info!("The server has stopped.");
+        next.set(ServerState::Stopped);
+    }
 }
@@ -1,30 +1,128 @@
-use std::net::SocketAddr;
+use std::{net::SocketAddr, path::PathBuf};
 
 use bevy::prelude::*;
 use clap::Parser;
-use imm_sim_server::{ImmSimServerPlugin, ServerLifecycleCmd};
+use imm_sim_server::{
+    ImmSimServerPlugin, ServerLifecycleCmd, auth,
+    config::{ConfigPath, NetworkConfig, ServerConfig},
+    console::ConsolePlugin,
+};
+use imm_sim_shared::level::LevelPath;
 
+/// Every argument given here overrides the same setting in the config file.
 #[derive(Parser)]
 pub struct Args {
-    bind_addr: SocketAddr,
+    bind_addr: Option<SocketAddr>,
     room_password: Option<String>,
+
+    /// A TOML file to read settings from. See the `config` module for what it may hold.
+    #[arg(long)]
+    config: Option<PathBuf>,
+
+    /// Run in secure mode, only accepting clients with a connect token signed by the private key in
+    /// this file. Keys and tokens can be created with the `issue_token` binary.
+    #[arg(long)]
+    private_key_file: Option<PathBuf>,
+
+    /// The level file to load. Clients must have the same level to join.
+    #[arg(long)]
+    level: Option<PathBuf>,
+
+    /// The file bans are kept in, which is created when the first ban is made. Without one, bans only
+    /// last until the server stops.
+    #[arg(long)]
+    ban_file: Option<PathBuf>,
+
+    #[arg(long)]
+    max_clients: Option<usize>,
 }
 
-fn main() {
+/// The network settings to start the server with, once the arguments have been applied.
+#[derive(Resource)]
+struct StartupNetworkConfig(NetworkConfig);
+
+fn main() -> AppExit {
+    let args = Args::parse();
+
+    let mut config = match args.config.as_deref().map(ServerConfig::load) {
+        Some(Ok(config)) => config,
+        Some(Err(e)) => {
+            eprintln!("Error loading the server config: {e}");
+            return AppExit::error();
+        }
+        None => ServerConfig::default(),
+    };
+
+    let Args {
+        bind_addr,
+        room_password,
+        config: config_path,
+        private_key_file,
+        level,
+        ban_file,
+        max_clients,
+    } = args;
+
+    let network = &mut config.network;
+    network.bind_addr = bind_addr.or(network.bind_addr);
+    network.room_password = room_password.or(network.room_password.take());
+    network.private_key_file = private_key_file.or(network.private_key_file.take());
+    network.ban_file = ban_file.or(network.ban_file.take());
+    network.max_clients = max_clients.unwrap_or(network.max_clients);
+    config.gameplay.level = level.unwrap_or(config.gameplay.level);
+
+    if config.network.bind_addr.is_none() {
+        eprintln!("No address to bind to was given, either as an argument or in the config file.");
+        return AppExit::error();
+    }
+
     App::new()
-        .add_plugins(ImmSimServerPlugin::standalone())
+        .add_plugins((ImmSimServerPlugin::standalone(), ConsolePlugin))
+        .insert_resource(LevelPath(config.gameplay.level.clone()))
+        .insert_resource(ConfigPath(config_path))
+        .insert_resource(StartupNetworkConfig(config.network))
+        .insert_resource(config.gameplay)
+        .insert_resource(config.logging)
         .add_systems(Startup, startup)
-        .run();
+        .run()
 }
 
-fn startup(mut writer: EventWriter<ServerLifecycleCmd>) {
-    let Args {
+fn startup(
+    config: Res<StartupNetworkConfig>,
+    mut writer: EventWriter<ServerLifecycleCmd>,
+    mut exit: EventWriter<AppExit>,
+) {
+    let NetworkConfig {
         bind_addr,
+        max_clients,
         room_password,
-    } = Args::parse();
+        private_key_file,
+        ban_file,
+        ..
+    } = &config.0;
+
+    let private_key = match private_key_file.as_deref().map(auth::read_private_key) {
+        Some(Ok(key)) => Some(key),
+        Some(Err(e)) => {
+            error!("Could not read the private key file: {e}");
+            exit.send(AppExit::error());
+            return;
+        }
+        None => None,
+    };
+
+    let Some(handshake_limits) = config.0.handshake_limits() else {
+        error!("The handshake timeout must be a positive number of seconds.");
+        exit.send(AppExit::error());
+        return;
+    };
 
     writer.send(ServerLifecycleCmd::StartServer {
-        bind_addr,
-        room_password,
+        bind_addr: bind_addr.expect("Bind address is checked before the app is built"),
+        room_password: room_password.clone(),
+        private_key,
+        ban_file: ban_file.clone(),
+        max_clients: *max_clients,
+        handshake_limits,
     });
 }
@@ -1,6 +1,13 @@
+use avian3d::prelude::*;
 use bevy::prelude::*;
-use bevy_replicon::server::ServerSet;
-use imm_sim_shared::physics::components::transform::ReplicatedTransform;
+use bevy_replicon::{prelude::*, server::ServerSet};
+use imm_sim_shared::physics::{
+    components::{
+        transform::{ReplicatedTransform, S2CServerTick, TransformTick},
+        velocity::ReplicatedLinearVelocity,
+    },
+    ground::GroundDetectionPlugin,
+};
 
 use crate::ServerState;
 
@@ -8,17 +15,80 @@ pub struct ServerPhysicsPlugin;
 
 impl Plugin for ServerPhysicsPlugin {
     fn build(&self, app: &mut App) {
+        // The client may have already added this when the server runs alongside it.
+        if !app.is_plugin_added::<GroundDetectionPlugin>() {
+            app.add_plugins(GroundDetectionPlugin);
+        }
+
+        app.init_resource::<ServerTick>().add_systems(
+            FixedLast,
+            advance_server_tick.run_if(in_state(ServerState::Running)),
+        );
+
         app.add_systems(
             PostUpdate,
-            mirror_transforms
+            (
+                mirror_transforms,
+                mirror_velocities,
+                send_server_tick.run_if(resource_changed::<ServerTick>),
+            )
                 .run_if(in_state(ServerState::Running))
                 .before(ServerSet::Send),
         );
     }
 }
 
-fn mirror_transforms(mut query: Query<(&Transform, &mut ReplicatedTransform)>) {
-    for (transform, mut replica) in query.iter_mut() {
-        *replica = (*transform).into();
+/// The number of fixed ticks the server has run, used to stamp every [`ReplicatedTransform`].
+#[derive(Default, Resource)]
+pub struct ServerTick(pub u32);
+
+fn advance_server_tick(mut tick: ResMut<ServerTick>) {
+    tick.0 = tick.0.wrapping_add(1);
+}
+
+fn send_server_tick(tick: Res<ServerTick>, mut writer: EventWriter<ToClients<S2CServerTick>>) {
+    writer.send(ToClients {
+        mode: SendMode::Broadcast,
+        event: S2CServerTick(tick.0),
+    });
+}
+
+/// Marks an entity whose [`Transform`] has stopped changing, once clients have been sent a final
+/// snapshot saying so.
+#[derive(Component)]
+struct AtRest;
+
+/// Copy every [`Transform`] that has changed into its [`ReplicatedTransform`], stamped with the
+/// current tick. Entities at rest are left untouched, such that they aren't sent again.
+fn mirror_transforms(
+    tick: Res<ServerTick>,
+    mut query: Query<(
+        Entity,
+        &Transform,
+        &mut ReplicatedTransform,
+        &mut TransformTick,
+        Has<AtRest>,
+    )>,
+    mut commands: Commands,
+) {
+    for (entity, transform, mut replica, mut replica_tick, is_at_rest) in query.iter_mut() {
+        if replica.set_if_neq((*transform).into()) {
+            replica_tick.set_if_neq(TransformTick(tick.0));
+
+            if is_at_rest {
+                commands.entity(entity).remove::<AtRest>();
+            }
+        } else if !is_at_rest && replica_tick.0 != tick.0 {
+            // A whole tick has passed without it moving. Stamp where it stopped once more, so that
+            // clients hold it there rather than extrapolating past it.
+            replica_tick.0 = tick.0;
+            commands.entity(entity).insert(AtRest);
+        }
+    }
+}
+
+fn mirror_velocities(mut query: Query<(&LinearVelocity, &mut ReplicatedLinearVelocity)>) {
+    for (lin_vel, mut replica) in query.iter_mut() {
+        replica.set_if_neq(ReplicatedLinearVelocity(lin_vel.0));
     }
 }
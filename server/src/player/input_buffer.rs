@@ -0,0 +1,232 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    time::Duration,
+};
+
+use bevy::prelude::*;
+use bevy_replicon::prelude::*;
+use imm_sim_shared::{
+    disconnect::{DisconnectReason, S2CDisconnectNotice},
+    player::messages::client_input::C2SInputEvent,
+};
+
+use crate::connection::{disconnect::ScheduledDisconnects, tracking::ConnectionTracker};
+
+/// The most inputs held for a client awaiting their tick. Should a client's clock run slightly
+/// fast, the oldest are dropped rather than letting the delay grow.
+const MAX_BUFFERED_INPUTS: usize = 4;
+
+/// The span of time over which a client's inputs are counted.
+const INPUT_WINDOW: Duration = Duration::from_secs(10);
+
+/// How many times over the tick rate a client may send inputs within a window before the rest are
+/// ignored as flooding. Some slack is needed, as inputs sent unreliably may arrive in bursts.
+const MAX_INPUT_RATE_FACTOR: f32 = 2.0;
+
+/// How many invalid inputs, or ignored floods, a client may send within a window before it is
+/// disconnected.
+const MAX_VIOLATIONS: u32 = 30;
+
+/// Inputs received from each client, of which [`handle_player_inputs`] applies one per tick.
+///
+/// [`handle_player_inputs`]: super::handle_player_inputs
+#[derive(Default, Resource)]
+pub struct InputBuffers {
+    clients: HashMap<u64, ClientInputs>,
+}
+
+impl InputBuffers {
+    /// Take the oldest input buffered for each client.
+    pub fn pop_each(&mut self) -> impl Iterator<Item = (u64, C2SInputEvent)> + '_ {
+        self.clients.iter_mut().filter_map(|(client_id, inputs)| {
+            let input = inputs.pending.pop_front()?;
+            inputs.last_taken = input.sequence;
+
+            Some((*client_id, input))
+        })
+    }
+}
+
+#[derive(Default)]
+struct ClientInputs {
+    /// Ordered by sequence number, as inputs may arrive out of order.
+    pending: VecDeque<C2SInputEvent>,
+    /// The sequence number of the last input taken from the buffer. Anything older is stale.
+    last_taken: u32,
+
+    window_started_at: Duration,
+    received_in_window: u32,
+    violations_in_window: u32,
+}
+
+impl ClientInputs {
+    fn push(&mut self, input: C2SInputEvent) {
+        if input.sequence <= self.last_taken {
+            return;
+        }
+
+        let index = self
+            .pending
+            .partition_point(|pending| pending.sequence < input.sequence);
+
+        // A duplicate of an input already waiting is of no use.
+        if self
+            .pending
+            .get(index)
+            .is_some_and(|pending| pending.sequence == input.sequence)
+        {
+            return;
+        }
+
+        self.pending.insert(index, input);
+
+        if self.pending.len() > MAX_BUFFERED_INPUTS {
+            self.pending.pop_front();
+        }
+    }
+}
+
+/// Validate every input received this tick and buffer it for its client, disconnecting any client
+/// that keeps sending invalid input or floods the server with it.
+pub fn buffer_player_inputs(
+    mut reader: EventReader<FromClient<C2SInputEvent>>,
+    mut notices: EventWriter<ToClients<S2CDisconnectNotice>>,
+    time: Res<Time<Real>>,
+    fixed_time: Res<Time<Fixed>>,
+    conn_tracker: Res<ConnectionTracker>,
+    mut buffers: ResMut<InputBuffers>,
+    mut scheduled_disconnects: ResMut<ScheduledDisconnects>,
+) {
+    let now = time.elapsed();
+    let max_inputs_per_window = (MAX_INPUT_RATE_FACTOR * INPUT_WINDOW.as_secs_f32()
+        / fixed_time.timestep().as_secs_f32()) as u32;
+
+    // Forget any clients who have since disconnected.
+    buffers
+        .clients
+        .retain(|client_id, _| conn_tracker.get_avatar(*client_id).is_some());
+
+    for FromClient { client_id, event } in reader.read() {
+        let Some(display_name) = conn_tracker.get_display_name(client_id.get()) else {
+            debug!("Unexepected input from client {client_id:?}. This client is not tracked.");
+            continue;
+        };
+
+        let inputs = buffers.clients.entry(client_id.get()).or_default();
+
+        if now.saturating_sub(inputs.window_started_at) >= INPUT_WINDOW {
+            inputs.window_started_at = now;
+            inputs.received_in_window = 0;
+            inputs.violations_in_window = 0;
+        }
+        inputs.received_in_window += 1;
+
+        let violation = if inputs.received_in_window > max_inputs_per_window {
+            Some("is sending more inputs than the tick rate allows".to_owned())
+        } else if let Err(e) = event.validate() {
+            Some(format!("sent invalid input: {e}"))
+        } else {
+            inputs.push(*event);
+            None
+        };
+
+        let Some(violation) = violation else {
+            continue;
+        };
+        inputs.violations_in_window += 1;
+
+        // Only the first violation in each window is logged, so as not to flood the log in turn.
+        if inputs.violations_in_window == 1 {
+            warn!("{display_name} (client {client_id:?}) {violation}.");
+        }
+
+        if inputs.violations_in_window == MAX_VIOLATIONS {
+            warn!("Disconnecting {display_name}, who keeps sending invalid or excessive input.");
+            scheduled_disconnects.force_disconnect(
+                client_id.get(),
+                DisconnectReason::InvalidInput,
+                &mut notices,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use imm_sim_shared::player::messages::client_input::DigitalInput;
+
+    use super::*;
+
+    fn input(sequence: u32) -> C2SInputEvent {
+        C2SInputEvent {
+            sequence,
+            translation_strafe: 0.0,
+            translation_walk: 0.0,
+            rotation_pitch: 0.0,
+            rotation_yaw: 0.0,
+            crouch_button: DigitalInput::NotPressed,
+            jump_button: DigitalInput::NotPressed,
+        }
+    }
+
+    fn pending(inputs: &ClientInputs) -> Vec<u32> {
+        inputs.pending.iter().map(|input| input.sequence).collect()
+    }
+
+    #[test]
+    fn push_orders_inputs_by_sequence() {
+        let mut inputs = ClientInputs::default();
+        for sequence in [3, 1, 2] {
+            inputs.push(input(sequence));
+        }
+
+        assert_eq!(pending(&inputs), [1, 2, 3]);
+    }
+
+    #[test]
+    fn push_ignores_duplicates() {
+        let mut inputs = ClientInputs::default();
+        for sequence in [1, 2, 2, 1] {
+            inputs.push(input(sequence));
+        }
+
+        assert_eq!(pending(&inputs), [1, 2]);
+    }
+
+    #[test]
+    fn push_ignores_inputs_already_taken() {
+        let mut buffers = InputBuffers::default();
+        let inputs = buffers.clients.entry(7).or_default();
+        inputs.push(input(5));
+
+        assert_eq!(
+            buffers
+                .pop_each()
+                .map(|(client_id, input)| (client_id, input.sequence))
+                .collect::<Vec<_>>(),
+            [(7, 5)]
+        );
+
+        let inputs = buffers.clients.get_mut(&7).unwrap();
+        inputs.push(input(4));
+        inputs.push(input(5));
+        inputs.push(input(6));
+
+        assert_eq!(pending(inputs), [6]);
+    }
+
+    #[test]
+    fn push_drops_the_oldest_when_full() {
+        let mut inputs = ClientInputs::default();
+        for sequence in 1..=MAX_BUFFERED_INPUTS as u32 + 2 {
+            inputs.push(input(sequence));
+        }
+
+        assert_eq!(inputs.pending.len(), MAX_BUFFERED_INPUTS);
+        assert_eq!(inputs.pending.front().map(|input| input.sequence), Some(3));
+        assert_eq!(
+            inputs.pending.back().map(|input| input.sequence),
+            Some(MAX_BUFFERED_INPUTS as u32 + 2)
+        );
+    }
+}
@@ -1,57 +1,103 @@
+use std::time::Duration;
+
 use avian3d::prelude::*;
-use bevy::prelude::*;
+use bevy::{prelude::*, time::common_conditions::on_real_timer};
+use bevy_renet::renet::RenetServer;
 use bevy_replicon::prelude::*;
-use imm_sim_shared::player::{
-    components::PlayerAvatarColor,
-    messages::client_input::{C2SCommand, C2SInputEvent},
+use imm_sim_shared::{
+    physics::{
+        components::movement::{
+            Crouching, Grounded, HeadBlocked, JumpImpulse, MovementAcceleration,
+        },
+        ground::GroundDetectionSet,
+    },
+    player::{
+        components::{AcknowledgedInput, LookDirection, PlayerAvatarColor, RoundTripTime},
+        messages::client_input::{C2SCommand, C2SInputEvent},
+        movement,
+    },
 };
 
+use self::input_buffer::{InputBuffers, buffer_player_inputs};
 use crate::{ServerState, connection::tracking::ConnectionTracker};
 
-pub struct ServerPlayerPlugin;
+mod input_buffer;
 
-const MOVEMENT_ACCELERATION: f32 = 25.0;
+/// How often each player's [`RoundTripTime`] is refreshed.
+const ROUND_TRIP_TIME_INTERVAL: Duration = Duration::from_secs(1);
+
+pub struct ServerPlayerPlugin;
 
 impl Plugin for ServerPlayerPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
+        app.init_resource::<InputBuffers>().add_systems(
             FixedUpdate,
-            handle_player_inputs.run_if(in_state(ServerState::Running)),
+            (buffer_player_inputs, handle_player_inputs)
+                .chain()
+                .run_if(in_state(ServerState::Running))
+                .after(GroundDetectionSet),
         );
 
         app.add_systems(
             FixedUpdate,
             handle_player_commands.run_if(in_state(ServerState::Running)),
         );
+
+        app.add_systems(
+            Update,
+            measure_round_trip_times.run_if(
+                in_state(ServerState::Running).and(on_real_timer(ROUND_TRIP_TIME_INTERVAL)),
+            ),
+        );
     }
 }
 
+/// Apply one buffered input per client each tick, such that sending more often than the tick rate
+/// doesn't make anyone move faster.
 fn handle_player_inputs(
-    mut reader: EventReader<FromClient<C2SInputEvent>>,
+    mut buffers: ResMut<InputBuffers>,
 
     time: Res<Time>,
     conn_tracker: Res<ConnectionTracker>,
 
-    mut query: Query<(&Transform, &mut LinearVelocity)>,
+    mut query: Query<(
+        &mut Rotation,
+        &mut LookDirection,
+        &mut LinearVelocity,
+        &mut AcknowledgedInput,
+        &JumpImpulse,
+        &MovementAcceleration,
+        Has<Grounded>,
+        Has<Crouching>,
+        Has<HeadBlocked>,
+    )>,
+
+    mut commands: Commands,
 ) {
-    for FromClient { client_id, event } in reader.read() {
-        let client_id = client_id.get();
+    for (client_id, event) in buffers.pop_each() {
         let Some(avatar) = conn_tracker.get_avatar(client_id) else {
-            debug!("Unexepected input from client {client_id}. This client is not tracked.");
             continue;
         };
 
         let C2SInputEvent {
-            translation_strafe,
-            translation_walk,
-            // rotation_pitch,
-            // rotation_yaw,
-            // crouch_button,
-            // jump_button,
+            sequence,
+            rotation_pitch,
+            rotation_yaw,
+            crouch_button,
             ..
-        } = event;
+        } = &event;
 
-        let (transform, mut lin_vel) = match query.get_mut(avatar) {
+        let (
+            mut rotation,
+            mut look,
+            mut lin_vel,
+            mut acknowledged,
+            jump_impulse,
+            acceleration,
+            is_grounded,
+            is_crouching,
+            is_head_blocked,
+        ) = match query.get_mut(avatar) {
             Ok(out) => out,
             Err(e) => {
                 error!("Player {client_id}'s avatar is missing a component: {e}");
@@ -59,11 +105,40 @@ fn handle_player_inputs(
             }
         };
 
-        let current_direction = transform.rotation;
-        let movement_direction =
-            current_direction * Vec3::new(*translation_strafe, 0.0, *translation_walk);
+        // Inputs are sent unreliably, so they may arrive out of order. Anything older than what has
+        // already been applied is stale.
+        if *sequence <= acknowledged.sequence {
+            continue;
+        }
+        acknowledged.sequence = *sequence;
+
+        // The yaw turns the whole avatar, while the pitch is only kept on the [`LookDirection`].
+        look.rotate(*rotation_pitch, *rotation_yaw);
+        rotation.0 = look.body_rotation();
 
-        lin_vel.0 += movement_direction * MOVEMENT_ACCELERATION * time.delta_secs();
+        // [`Grounded`] is removed straight away so that a second jump can't be queued up before the
+        // next ground check.
+        if movement::wants_jump(&event, is_grounded) {
+            lin_vel.y += jump_impulse.0;
+            commands.entity(avatar).remove::<Grounded>();
+        }
+
+        // Crouching follows the held state of the button rather than the press and release edges,
+        // such that a single dropped input doesn't leave the player stuck in the wrong stance. A
+        // player can't stand back up while there is a ceiling in the way.
+        if crouch_button.is_pressed() && !is_crouching {
+            commands.entity(avatar).insert(Crouching);
+        } else if !crouch_button.is_pressed() && is_crouching && !is_head_blocked {
+            commands.entity(avatar).remove::<Crouching>();
+        }
+
+        movement::accelerate(
+            &event,
+            rotation.0,
+            acceleration.0,
+            time.delta_secs(),
+            &mut lin_vel.0,
+        );
     }
 }
 
@@ -94,3 +169,29 @@ fn handle_player_commands(
         }
     }
 }
+
+/// Copy each client's round-trip time, as measured by renet, onto their avatar.
+///
+/// The listening server's own player has no connection to measure, so it is left at zero.
+fn measure_round_trip_times(
+    server: Res<RenetServer>,
+    conn_tracker: Res<ConnectionTracker>,
+    mut query: Query<&mut RoundTripTime>,
+) {
+    for (client_id, _) in conn_tracker.iter() {
+        let Ok(network_info) = server.network_info(client_id) else {
+            continue;
+        };
+        let Some(mut round_trip_time) = conn_tracker
+            .get_avatar(client_id)
+            .and_then(|avatar| query.get_mut(avatar).ok())
+        else {
+            continue;
+        };
+
+        // Renet measures in seconds. Only write on change, so as not to replicate it needlessly.
+        round_trip_time.set_if_neq(RoundTripTime {
+            millis: (network_info.rtt * 1000.0).round() as u32,
+        });
+    }
+}
@@ -0,0 +1,8 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// A message from the server's operator, shown to every connected player.
+#[derive(Clone, Debug, Deserialize, Event, Serialize)]
+pub struct S2CAnnouncement {
+    pub message: String,
+}
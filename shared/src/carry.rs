@@ -0,0 +1,38 @@
+use bevy::{
+    ecs::entity::{EntityMapper, MapEntities},
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+
+/// How far, in metres, in front of a player's eyes a carried object is held.
+pub const HOLD_DISTANCE: f32 = 1.2;
+
+/// The heaviest object, in kilograms, that a player can pick up.
+pub const MAX_CARRY_MASS: f32 = 20.0;
+
+/// Marks a physics object as being carried by a player, such that every client knows who holds it.
+///
+/// The server moves it to stay in front of the carrier's eyes, following their [`LookDirection`],
+/// and drops it should it get caught on something.
+///
+/// [`LookDirection`]: crate::player::components::LookDirection
+#[derive(Clone, Component, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Carried {
+    /// The avatar of the player carrying it.
+    pub carrier: Entity,
+}
+
+impl MapEntities for Carried {
+    fn map_entities<M: EntityMapper>(&mut self, entity_mapper: &mut M) {
+        self.carrier = entity_mapper.map_entity(self.carrier);
+    }
+}
+
+/// Where a carried object is held, given the carrier's eyes and the direction they look in.
+pub fn hold_translation(eye: Vec3, direction: Dir3) -> Vec3 {
+    eye + direction * HOLD_DISTANCE
+}
+
+/// Sent when the player presses the throw button while carrying something.
+#[derive(Clone, Copy, Debug, Deserialize, Event, Serialize)]
+pub struct C2SThrow;
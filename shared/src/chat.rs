@@ -0,0 +1,37 @@
+use std::fmt::{self, Display};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// The most characters a chat message may hold. Longer messages are refused by the server.
+pub const MAX_CHAT_MESSAGE_LEN: usize = 256;
+
+/// A line of chat typed by a player, to be passed on to everyone else by the server.
+#[derive(Clone, Debug, Deserialize, Event, Serialize)]
+pub struct C2SChatMessage {
+    pub message: String,
+}
+
+/// A line of chat to show, either passed on from a player or from the server itself.
+#[derive(Clone, Debug, Deserialize, Event, Serialize)]
+pub struct S2CChatMessage {
+    pub sender: ChatSender,
+    pub message: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum ChatSender {
+    /// A player, by their display name.
+    Player(String),
+    /// The server, as for players joining or leaving, or messages being refused.
+    System,
+}
+
+impl Display for S2CChatMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.sender {
+            ChatSender::Player(display_name) => write!(f, "<{display_name}> {}", self.message),
+            ChatSender::System => write!(f, "* {}", self.message),
+        }
+    }
+}
@@ -0,0 +1,31 @@
+use std::fmt::{self, Display};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Sent to a client shortly before the server disconnects it, such that it can tell the player why.
+#[derive(Clone, Debug, Deserialize, Event, Serialize)]
+pub struct S2CDisconnectNotice {
+    pub reason: DisconnectReason,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum DisconnectReason {
+    /// The server is shutting down, or restarting.
+    ServerStopping,
+    Kicked,
+    Banned,
+    /// The client kept sending input that no unmodified client would.
+    InvalidInput,
+}
+
+impl Display for DisconnectReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ServerStopping => write!(f, "The server is shutting down."),
+            Self::Kicked => write!(f, "You were kicked from the server."),
+            Self::Banned => write!(f, "You were banned from the server."),
+            Self::InvalidInput => write!(f, "Your client sent too much invalid input."),
+        }
+    }
+}
@@ -1,3 +1,5 @@
+use std::fmt::{self, Display};
+
 use bevy::prelude::*;
 use serde::{Deserialize, Serialize};
 
@@ -5,10 +7,102 @@ use serde::{Deserialize, Serialize};
 pub struct C2SHandshakeStart {
     pub display_name: String,
     pub room_password: Option<String>,
+
+    /// The [`CRATE_VERSION`](crate::CRATE_VERSION) of the client. This must match the server's
+    /// exactly.
+    pub crate_version: String,
+
+    /// The [`content_hash`](crate::level::definition::LevelDefinition::content_hash) of the level
+    /// the client has loaded. This must match the server's, else the two physics worlds differ.
+    pub content_hash: u64,
+
+    /// The session token given when this client last joined the server, if it has done so under
+    /// the same display name. Should its avatar still be awaiting its return, the client takes it
+    /// back rather than spawning afresh.
+    pub session_token: Option<u64>,
 }
 
 #[derive(Debug, Deserialize, Event, Serialize)]
 pub enum S2CHandshakeResult {
-    ConnectionAccepted { client_id: u64 },
-    ConnectionRejected { reason: String },
+    ConnectionAccepted {
+        client_id: u64,
+        /// A secret the client presents should it need to reconnect. See
+        /// [`C2SHandshakeStart::session_token`].
+        session_token: u64,
+        /// The rate, in hertz, of the server's fixed time-step, which the client must run its own
+        /// at for prediction and interpolation to line up.
+        tick_rate_hz: f64,
+    },
+    ConnectionRejected {
+        reason: HandshakeRejection,
+    },
+}
+
+/// Why the server refused a handshake.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum HandshakeRejection {
+    /// The client and server were built from different versions of the game.
+    VersionMismatch {
+        server: String,
+        client: String,
+    },
+    /// The client has loaded a different level to the server.
+    ContentMismatch,
+    PasswordRequired,
+    IncorrectPassword,
+    DisplayNameInUse(String),
+    /// Too many other clients are part way through joining.
+    ServerBusy,
+    /// The handshake was not completed in time.
+    TimedOut,
+    Banned {
+        reason: Option<String>,
+        /// How many seconds are left until the ban lifts, if it ever does. The server counts this
+        /// down itself, such that the two machines' clocks needn't agree.
+        remaining_secs: Option<u64>,
+    },
+}
+
+impl Display for HandshakeRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::VersionMismatch { server, client } => write!(
+                f,
+                "The server is running version {server}, but you are running version {client}."
+            ),
+            Self::ContentMismatch => write!(
+                f,
+                "Your level differs from the server's. Make sure you have chosen the same level file."
+            ),
+            Self::PasswordRequired => write!(f, "This server requires a password to join."),
+            Self::IncorrectPassword => write!(f, "The password you gave is not correct."),
+            Self::DisplayNameInUse(display_name) => write!(
+                f,
+                "The requested display name `{display_name}` is already in use on this server."
+            ),
+            Self::ServerBusy => write!(f, "The server is busy. Try again in a moment."),
+            Self::TimedOut => write!(f, "The handshake took too long to complete."),
+            Self::Banned {
+                reason,
+                remaining_secs,
+            } => {
+                write!(f, "You are banned from this server")?;
+
+                if let Some(remaining_secs) = remaining_secs {
+                    let remaining_minutes = remaining_secs.div_ceil(60);
+
+                    match remaining_minutes {
+                        0..60 => write!(f, " for another {remaining_minutes} minute(s)")?,
+                        60..1440 => write!(f, " for another {} hour(s)", remaining_minutes / 60)?,
+                        _ => write!(f, " for another {} day(s)", remaining_minutes / 1440)?,
+                    }
+                }
+
+                match reason {
+                    Some(reason) => write!(f, ": {reason}"),
+                    None => write!(f, "."),
+                }
+            }
+        }
+    }
 }
@@ -0,0 +1,78 @@
+use avian3d::prelude::*;
+use bevy::{
+    ecs::entity::{EntityMapper, MapEntities},
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::physics::components::collision::CoLayer;
+
+/// How far, in metres, past an [`Interactable`]'s own range the server still accepts a use. This
+/// covers the client's camera having moved a little further than the server has seen.
+pub const INTERACTION_RANGE_TOLERANCE: f32 = 0.5;
+
+/// How far above a standing player's centre their eyes are.
+const STANDING_EYE_HEIGHT: f32 = 0.4;
+
+/// How far above a crouching player's centre their eyes are.
+const CROUCHING_EYE_HEIGHT: f32 = -0.2;
+
+/// Something in the world that a player can use by looking at it and pressing the use key.
+///
+/// Gameplay systems on the server decide what using it does.
+#[derive(Clone, Component, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Interactable {
+    /// Shown to the player while they look at it, such as "Open door".
+    pub prompt: String,
+
+    /// How far, in metres, from a player's eyes it may be used.
+    pub range: f32,
+}
+
+/// Sent when the player presses the use key while looking at an [`Interactable`]. The server checks
+/// that the target is in range and in sight before acting on it.
+#[derive(Clone, Copy, Debug, Deserialize, Event, Serialize)]
+pub struct C2SInteract {
+    pub target: Entity,
+}
+
+impl MapEntities for C2SInteract {
+    fn map_entities<M: EntityMapper>(&mut self, entity_mapper: &mut M) {
+        self.target = entity_mapper.map_entity(self.target);
+    }
+}
+
+/// Where a player's eyes are, given the translation of their avatar.
+///
+/// The client's camera sits here, and the server casts rays from here, such that both agree on what
+/// a player can see.
+pub fn eye_translation(body_translation: Vec3, is_crouching: bool) -> Vec3 {
+    let eye_height = if is_crouching {
+        CROUCHING_EYE_HEIGHT
+    } else {
+        STANDING_EYE_HEIGHT
+    };
+
+    body_translation + Vec3::Y * eye_height
+}
+
+/// Cast a ray from a player's eyes, returning the entity that was hit and how far away it is.
+///
+/// Players are looked straight through, such that they neither block the ray nor can be hit by it.
+/// Should the ray hit a collider that belongs to a rigid body, the rigid body is returned instead.
+pub fn cast_interaction_ray(
+    spatial_query: &SpatialQuery,
+    collider_parents: &Query<&ColliderParent>,
+    eye: Vec3,
+    direction: Dir3,
+    max_distance: f32,
+) -> Option<(Entity, f32)> {
+    let filter = SpatialQueryFilter::from_mask([CoLayer::Environment, CoLayer::Pickup]);
+    let hit = spatial_query.cast_ray(eye, direction, max_distance, true, &filter)?;
+
+    let entity = collider_parents
+        .get(hit.entity)
+        .map_or(hit.entity, ColliderParent::get);
+
+    Some((entity, hit.distance))
+}
@@ -0,0 +1,349 @@
+use std::fmt::{self, Display};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// How many slots wide the inventory grid is.
+pub const INVENTORY_COLUMNS: usize = 6;
+
+/// How many slots tall the inventory grid is.
+pub const INVENTORY_ROWS: usize = 4;
+
+pub const INVENTORY_SLOTS: usize = INVENTORY_COLUMNS * INVENTORY_ROWS;
+
+/// Something a player can carry. Items that are equal stack together.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum Item {
+    /// Unlocks every door with the given id.
+    Key { door_id: String },
+    /// Anything without a purpose of its own yet, known only by its name.
+    Misc { name: String },
+}
+
+impl Item {
+    /// The most of this item that fit in a single slot.
+    pub fn max_stack(&self) -> u32 {
+        match self {
+            Self::Key { .. } => 1,
+            Self::Misc { .. } => 16,
+        }
+    }
+}
+
+impl Display for Item {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Key { door_id } => write!(f, "Key ({door_id})"),
+            Self::Misc { name } => write!(f, "{name}"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ItemStack {
+    pub item: Item,
+    /// Always at least one, and at most the item's [`max_stack`](Item::max_stack).
+    pub count: u32,
+}
+
+/// The items a player carries, laid out in a grid of [`INVENTORY_SLOTS`] slots.
+///
+/// Replicon can only hide whole entities from a client, so rather than sitting on the player's
+/// avatar, this is kept on an entity of its own with the same [`OwnedByClient`], which only the
+/// owning client is sent. Only the server changes it; clients ask it to with a
+/// [`C2SInventoryCommand`].
+///
+/// [`OwnedByClient`]: crate::ownership::OwnedByClient
+#[derive(Clone, Component, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Inventory {
+    slots: Vec<Option<ItemStack>>,
+}
+
+impl Default for Inventory {
+    fn default() -> Self {
+        Self {
+            slots: vec![None; INVENTORY_SLOTS],
+        }
+    }
+}
+
+impl Inventory {
+    /// Every slot, row by row.
+    pub fn slots(&self) -> &[Option<ItemStack>] {
+        &self.slots
+    }
+
+    pub fn contains(&self, item: &Item) -> bool {
+        self.slots.iter().flatten().any(|stack| stack.item == *item)
+    }
+
+    /// Add as many of the given item as will fit, topping up existing stacks before filling empty
+    /// slots. Returns how many did not fit.
+    pub fn add(&mut self, item: Item, mut count: u32) -> u32 {
+        let max_stack = item.max_stack();
+
+        for stack in self.slots.iter_mut().flatten() {
+            if count == 0 {
+                return 0;
+            }
+            if stack.item == item {
+                let added = count.min(max_stack.saturating_sub(stack.count));
+                stack.count += added;
+                count -= added;
+            }
+        }
+
+        for slot in self.slots.iter_mut().filter(|slot| slot.is_none()) {
+            if count == 0 {
+                return 0;
+            }
+
+            let added = count.min(max_stack);
+            *slot = Some(ItemStack {
+                item: item.clone(),
+                count: added,
+            });
+            count -= added;
+        }
+
+        count
+    }
+
+    /// Take up to `count` items out of the given slot, returning what was taken.
+    pub fn remove(&mut self, slot: usize, count: u32) -> Result<ItemStack, InventoryError> {
+        let taken_slot = self
+            .slots
+            .get_mut(slot)
+            .ok_or(InventoryError::NoSuchSlot(slot))?;
+        let stack = taken_slot.as_mut().ok_or(InventoryError::EmptySlot(slot))?;
+
+        let taken = count.min(stack.count);
+        stack.count -= taken;
+        let item = stack.item.clone();
+
+        if stack.count == 0 {
+            *taken_slot = None;
+        }
+
+        Ok(ItemStack { item, count: taken })
+    }
+
+    /// Move the stack in one slot to another. Should the other slot hold the same item, as much as
+    /// fits is merged into it, and otherwise the two stacks swap places.
+    pub fn move_stack(&mut self, from: usize, to: usize) -> Result<(), InventoryError> {
+        for slot in [from, to] {
+            if slot >= self.slots.len() {
+                return Err(InventoryError::NoSuchSlot(slot));
+            }
+        }
+        if self.slots[from].is_none() {
+            return Err(InventoryError::EmptySlot(from));
+        }
+        if from == to {
+            return Ok(());
+        }
+
+        let (source, destination) = if from < to {
+            let (left, right) = self.slots.split_at_mut(to);
+            (&mut left[from], &mut right[0])
+        } else {
+            let (left, right) = self.slots.split_at_mut(from);
+            (&mut right[0], &mut left[to])
+        };
+
+        match (source.as_mut(), destination.as_mut()) {
+            (Some(moving), Some(existing)) if moving.item == existing.item => {
+                let merged = moving
+                    .count
+                    .min(existing.item.max_stack().saturating_sub(existing.count));
+                existing.count += merged;
+                moving.count -= merged;
+
+                if moving.count == 0 {
+                    *source = None;
+                }
+            }
+            _ => std::mem::swap(source, destination),
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum InventoryError {
+    NoSuchSlot(usize),
+    EmptySlot(usize),
+}
+
+impl Display for InventoryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoSuchSlot(slot) => write!(f, "there is no slot {slot}"),
+            Self::EmptySlot(slot) => write!(f, "slot {slot} is empty"),
+        }
+    }
+}
+
+impl std::error::Error for InventoryError {}
+
+/// Asks the server to rearrange the sender's [`Inventory`].
+#[derive(Clone, Copy, Debug, Deserialize, Event, Serialize)]
+pub enum C2SInventoryCommand {
+    /// See [`Inventory::move_stack`].
+    Move { from: usize, to: usize },
+    /// Drop everything in the given slot into the world, in front of the player.
+    Drop { slot: usize },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bottle() -> Item {
+        Item::Misc {
+            name: "Bottle".to_owned(),
+        }
+    }
+
+    fn key() -> Item {
+        Item::Key {
+            door_id: "vault".to_owned(),
+        }
+    }
+
+    fn stack(item: Item, count: u32) -> Option<ItemStack> {
+        Some(ItemStack { item, count })
+    }
+
+    #[test]
+    fn add_tops_up_stacks_before_filling_empty_slots() {
+        let mut inventory = Inventory::default();
+        assert_eq!(inventory.add(bottle(), 10), 0);
+        assert_eq!(inventory.add(key(), 1), 0);
+        assert_eq!(inventory.add(bottle(), 10), 0);
+
+        assert_eq!(inventory.slots()[0], stack(bottle(), 16));
+        assert_eq!(inventory.slots()[1], stack(key(), 1));
+        assert_eq!(inventory.slots()[2], stack(bottle(), 4));
+        assert!(inventory.contains(&key()));
+    }
+
+    #[test]
+    fn add_returns_what_does_not_fit() {
+        let mut inventory = Inventory::default();
+        assert_eq!(inventory.add(key(), INVENTORY_SLOTS as u32 + 3), 3);
+        assert!(
+            inventory
+                .slots()
+                .iter()
+                .all(|slot| *slot == stack(key(), 1))
+        );
+
+        assert_eq!(inventory.add(bottle(), 5), 5);
+    }
+
+    #[test]
+    fn remove_takes_part_or_all_of_a_stack() {
+        let mut inventory = Inventory::default();
+        inventory.add(bottle(), 5);
+
+        assert_eq!(
+            inventory.remove(0, 2),
+            Ok(ItemStack {
+                item: bottle(),
+                count: 2,
+            })
+        );
+        assert_eq!(inventory.slots()[0], stack(bottle(), 3));
+
+        assert_eq!(
+            inventory.remove(0, u32::MAX),
+            Ok(ItemStack {
+                item: bottle(),
+                count: 3,
+            })
+        );
+        assert_eq!(inventory.slots()[0], None);
+    }
+
+    #[test]
+    fn remove_rejects_empty_and_missing_slots() {
+        let mut inventory = Inventory::default();
+
+        assert_eq!(inventory.remove(0, 1), Err(InventoryError::EmptySlot(0)));
+        assert_eq!(
+            inventory.remove(INVENTORY_SLOTS, 1),
+            Err(InventoryError::NoSuchSlot(INVENTORY_SLOTS))
+        );
+    }
+
+    #[test]
+    fn move_stack_rejects_empty_and_missing_slots() {
+        let mut inventory = Inventory::default();
+        inventory.add(bottle(), 1);
+
+        assert_eq!(
+            inventory.move_stack(0, INVENTORY_SLOTS),
+            Err(InventoryError::NoSuchSlot(INVENTORY_SLOTS))
+        );
+        assert_eq!(
+            inventory.move_stack(INVENTORY_SLOTS, 0),
+            Err(InventoryError::NoSuchSlot(INVENTORY_SLOTS))
+        );
+        assert_eq!(
+            inventory.move_stack(1, 0),
+            Err(InventoryError::EmptySlot(1))
+        );
+    }
+
+    #[test]
+    fn move_stack_to_the_same_slot_does_nothing() {
+        let mut inventory = Inventory::default();
+        inventory.add(bottle(), 3);
+
+        assert_eq!(inventory.move_stack(0, 0), Ok(()));
+        assert_eq!(inventory.slots()[0], stack(bottle(), 3));
+    }
+
+    #[test]
+    fn move_stack_into_an_empty_slot_either_way() {
+        let mut inventory = Inventory::default();
+        inventory.add(bottle(), 3);
+
+        assert_eq!(inventory.move_stack(0, 5), Ok(()));
+        assert_eq!(inventory.slots()[0], None);
+        assert_eq!(inventory.slots()[5], stack(bottle(), 3));
+
+        assert_eq!(inventory.move_stack(5, 2), Ok(()));
+        assert_eq!(inventory.slots()[5], None);
+        assert_eq!(inventory.slots()[2], stack(bottle(), 3));
+    }
+
+    #[test]
+    fn move_stack_merges_up_to_the_max_stack() {
+        let mut inventory = Inventory::default();
+        inventory.slots[0] = stack(bottle(), 10);
+        inventory.slots[3] = stack(bottle(), 10);
+
+        assert_eq!(inventory.move_stack(3, 0), Ok(()));
+        assert_eq!(inventory.slots()[0], stack(bottle(), 16));
+        assert_eq!(inventory.slots()[3], stack(bottle(), 4));
+
+        inventory.slots[0] = stack(bottle(), 12);
+        assert_eq!(inventory.move_stack(3, 0), Ok(()));
+        assert_eq!(inventory.slots()[0], stack(bottle(), 16));
+        assert_eq!(inventory.slots()[3], None);
+    }
+
+    #[test]
+    fn move_stack_swaps_different_items() {
+        let mut inventory = Inventory::default();
+        inventory.slots[1] = stack(bottle(), 7);
+        inventory.slots[4] = stack(key(), 1);
+
+        assert_eq!(inventory.move_stack(4, 1), Ok(()));
+        assert_eq!(inventory.slots()[1], stack(key(), 1));
+        assert_eq!(inventory.slots()[4], stack(bottle(), 7));
+    }
+}
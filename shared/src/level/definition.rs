@@ -0,0 +1,642 @@
+use std::{
+    fmt::{self, Display},
+    fs, io,
+    path::Path,
+};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::inventory::Item;
+
+/// The contents of a level file, written in RON.
+///
+/// ```ron
+/// (
+///     spawn_points: [(translation: (0.0, 5.0, 0.0))],
+///     blocks: [
+///         (translation: (0.0, -0.25, 0.0), extents: (20.0, 0.5, 20.0), color: (1.0, 1.0, 1.0)),
+///     ],
+///     lights: [
+///         (kind: Directional, rotation: (-45.0, 30.0, 0.0), intensity: 4000.0),
+///     ],
+///     items: [
+///         (id: "bottle", item: Misc(name: "Bottle"), extents: (0.1, 0.3, 0.1), mass: 0.5),
+///     ],
+///     entities: [
+///         Prop((translation: (0.0, 2.0, 0.0), extents: (0.5, 0.5, 0.5), mass: 5.0)),
+///         Door((
+///             translation: (3.0, 1.0, 0.0),
+///             extents: (1.0, 2.0, 0.1),
+///             motion: Hinge(angle: 90.0),
+///             state: Locked,
+///         )),
+///         Pickup((item: "bottle", count: 2, translation: (-2.0, 1.0, 0.0))),
+///     ],
+/// )
+/// ```
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct LevelDefinition {
+    /// Where players may be placed when they join. At least one is required.
+    pub spawn_points: Vec<SpawnPointDefinition>,
+
+    /// Static level geometry, spawned alike by the server and client.
+    #[serde(default)]
+    pub blocks: Vec<BlockDefinition>,
+
+    /// Lights, which only the client renders.
+    #[serde(default)]
+    pub lights: Vec<LightDefinition>,
+
+    /// The items that may be found in this level, and how they look lying in the world.
+    #[serde(default)]
+    pub items: Vec<ItemDefinition>,
+
+    /// Gameplay entities, which are spawned by the server and replicated to clients.
+    #[serde(default)]
+    pub entities: Vec<EntityDefinition>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SpawnPointDefinition {
+    pub translation: Vec3,
+
+    /// The direction, in degrees about the vertical axis, that players face once spawned.
+    #[serde(default)]
+    pub yaw: f32,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct BlockDefinition {
+    pub translation: Vec3,
+    pub extents: Vec3,
+
+    /// Euler angles in degrees, applied in YXZ order.
+    #[serde(default)]
+    pub rotation: Vec3,
+
+    /// An sRGB color, with each channel from 0.0 to 1.0.
+    #[serde(default = "default_color")]
+    pub color: Vec3,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum LightKind {
+    Point,
+    Directional,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct LightDefinition {
+    pub kind: LightKind,
+
+    #[serde(default)]
+    pub translation: Vec3,
+
+    /// Euler angles in degrees, applied in YXZ order. Directional lights shine along -Z.
+    #[serde(default)]
+    pub rotation: Vec3,
+
+    #[serde(default = "default_color")]
+    pub color: Vec3,
+
+    /// Lumens for point lights, or lux for directional lights.
+    pub intensity: f32,
+}
+
+/// An item, along with how it looks and moves while lying in the world rather than being carried.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ItemDefinition {
+    /// The name pickups refer to this item by. Must be unique within the level.
+    pub id: String,
+    pub item: Item,
+
+    #[serde(default = "default_item_extents")]
+    pub extents: Vec3,
+
+    #[serde(default = "default_color")]
+    pub color: Vec3,
+
+    /// Mass in kilograms.
+    #[serde(default = "default_item_mass")]
+    pub mass: f32,
+}
+
+impl ItemDefinition {
+    /// How an item looks when it isn't given a definition of its own, such as one a player brought
+    /// from another level.
+    pub fn fallback(item: Item) -> Self {
+        Self {
+            id: String::new(),
+            item,
+            extents: default_item_extents(),
+            color: Vec3::splat(0.5),
+            mass: default_item_mass(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum EntityDefinition {
+    /// A dynamic box that can be pushed around.
+    Prop(PropDefinition),
+    /// A door which players can open and close, and which may be locked or jammed.
+    Door(DoorDefinition),
+    /// A stack of items lying in the world, which players can pick up.
+    Pickup(PickupDefinition),
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PropDefinition {
+    pub translation: Vec3,
+    pub extents: Vec3,
+
+    #[serde(default)]
+    pub rotation: Vec3,
+
+    #[serde(default = "default_color")]
+    pub color: Vec3,
+
+    /// Mass in kilograms.
+    pub mass: f32,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DoorDefinition {
+    /// A name that keys and scripts can find the door by. Need not be unique, such that a single
+    /// key may open several doors.
+    #[serde(default)]
+    pub id: Option<String>,
+
+    /// The centre of the door while closed.
+    pub translation: Vec3,
+    pub extents: Vec3,
+
+    #[serde(default)]
+    pub rotation: Vec3,
+
+    #[serde(default = "default_color")]
+    pub color: Vec3,
+
+    pub motion: DoorMotion,
+
+    /// How long, in seconds, the door takes to fully open or close.
+    #[serde(default = "default_door_open_secs")]
+    pub open_secs: f32,
+
+    /// The state the door starts in.
+    #[serde(default)]
+    pub state: DoorState,
+}
+
+/// How a door moves as it opens, relative to its closed position and rotation.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum DoorMotion {
+    /// Swings about a vertical hinge along the door's -X edge by the given angle, in degrees.
+    /// Positive angles swing the door towards -Z.
+    Hinge { angle: f32 },
+    /// Slides along the given offset.
+    Slide { offset: Vec3 },
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub enum DoorState {
+    #[default]
+    Closed,
+    Open,
+    /// Closed, and can't be opened until it is unlocked.
+    Locked,
+    /// Stuck wherever it was, and can't be moved until it is freed.
+    Jammed,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PickupDefinition {
+    /// The id of one of the level's [`ItemDefinition`]s.
+    pub item: String,
+
+    #[serde(default = "default_pickup_count")]
+    pub count: u32,
+
+    pub translation: Vec3,
+
+    #[serde(default)]
+    pub rotation: Vec3,
+}
+
+fn default_color() -> Vec3 {
+    Vec3::ONE
+}
+
+fn default_item_extents() -> Vec3 {
+    Vec3::splat(0.25)
+}
+
+fn default_item_mass() -> f32 {
+    1.0
+}
+
+fn default_pickup_count() -> u32 {
+    1
+}
+
+fn default_door_open_secs() -> f32 {
+    1.0
+}
+
+/// Convert a rotation given as Euler angles in degrees into a [`Quat`].
+pub fn rotation_from_degrees(degrees: Vec3) -> Quat {
+    let Vec3 { x, y, z } = degrees;
+    Quat::from_euler(
+        EulerRot::YXZ,
+        y.to_radians(),
+        x.to_radians(),
+        z.to_radians(),
+    )
+}
+
+/// Convert a color given as an sRGB [`Vec3`] into a [`Color`].
+pub fn color_from_srgb(color: Vec3) -> Color {
+    Color::srgb(color.x, color.y, color.z)
+}
+
+#[derive(Debug)]
+pub enum LevelError {
+    Io(io::Error),
+    Parse(ron::error::SpannedError),
+    /// The level parsed correctly, but holds values that cannot be used.
+    Invalid(Vec<String>),
+}
+
+impl Display for LevelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "could not read level file: {e}"),
+            Self::Parse(e) => write!(f, "could not parse level file: {e}"),
+            Self::Invalid(problems) => {
+                write!(f, "level file is invalid:")?;
+                for problem in problems {
+                    write!(f, "\n  - {problem}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for LevelError {}
+
+impl LevelDefinition {
+    /// Read, parse and validate the level file at the given path.
+    pub fn load(path: &Path) -> Result<Self, LevelError> {
+        let contents = fs::read_to_string(path).map_err(LevelError::Io)?;
+        Self::parse(&contents)
+    }
+
+    /// Parse and validate a level from the contents of a level file.
+    pub fn parse(contents: &str) -> Result<Self, LevelError> {
+        let level: Self = ron::from_str(contents).map_err(LevelError::Parse)?;
+        level.validate()?;
+
+        Ok(level)
+    }
+
+    /// The item definition with the given id.
+    pub fn item_definition(&self, id: &str) -> Option<&ItemDefinition> {
+        self.items.iter().find(|definition| definition.id == id)
+    }
+
+    /// The first item definition for the given item, should the level define it.
+    pub fn item_definition_for(&self, item: &Item) -> Option<&ItemDefinition> {
+        self.items
+            .iter()
+            .find(|definition| definition.item == *item)
+    }
+
+    /// A stable hash of this level's contents, used to check that a client has loaded the same
+    /// level as the server.
+    ///
+    /// This hashes the level as re-serialized, so comments and formatting in the file don't matter.
+    pub fn content_hash(&self) -> u64 {
+        const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+        const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
+        let canonical = ron::to_string(self).expect("Level definitions are always serializable");
+
+        // FNV-1a, as the standard library makes no promise that its hashers are stable between
+        // builds.
+        canonical.bytes().fold(FNV_OFFSET_BASIS, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(FNV_PRIME)
+        })
+    }
+
+    /// Check for values that would parse fine, but break the game should they be used.
+    pub fn validate(&self) -> Result<(), LevelError> {
+        let mut problems = Vec::new();
+
+        if self.spawn_points.is_empty() {
+            problems.push("there must be at least one spawn point".to_owned());
+        }
+
+        for (i, spawn) in self.spawn_points.iter().enumerate() {
+            if !spawn.translation.is_finite() || !spawn.yaw.is_finite() {
+                problems.push(format!("spawn point {i} is not finite"));
+            }
+        }
+
+        for (i, block) in self.blocks.iter().enumerate() {
+            if !block.translation.is_finite() || !block.rotation.is_finite() {
+                problems.push(format!("block {i} has a non-finite transform"));
+            }
+            if !is_valid_extents(block.extents) {
+                problems.push(format!("block {i} must have positive, finite extents"));
+            }
+            if !is_valid_color(block.color) {
+                problems.push(format!("block {i} has a color outside of 0.0 to 1.0"));
+            }
+        }
+
+        for (i, light) in self.lights.iter().enumerate() {
+            if !light.translation.is_finite() || !light.rotation.is_finite() {
+                problems.push(format!("light {i} has a non-finite transform"));
+            }
+            if !light.intensity.is_finite() || light.intensity < 0.0 {
+                problems.push(format!("light {i} must have a non-negative intensity"));
+            }
+            if !is_valid_color(light.color) {
+                problems.push(format!("light {i} has a color outside of 0.0 to 1.0"));
+            }
+        }
+
+        for (i, definition) in self.items.iter().enumerate() {
+            if self.items[..i]
+                .iter()
+                .any(|other| other.id == definition.id)
+            {
+                problems.push(format!(
+                    "item `{}` is defined more than once",
+                    definition.id
+                ));
+            }
+            if !is_valid_extents(definition.extents) {
+                problems.push(format!(
+                    "item `{}` must have positive, finite extents",
+                    definition.id
+                ));
+            }
+            if !definition.mass.is_finite() || definition.mass <= 0.0 {
+                problems.push(format!(
+                    "item `{}` must have a positive mass",
+                    definition.id
+                ));
+            }
+            if !is_valid_color(definition.color) {
+                problems.push(format!(
+                    "item `{}` has a color outside of 0.0 to 1.0",
+                    definition.id
+                ));
+            }
+        }
+
+        for (i, entity) in self.entities.iter().enumerate() {
+            match entity {
+                EntityDefinition::Prop(prop) => {
+                    if !prop.translation.is_finite() || !prop.rotation.is_finite() {
+                        problems.push(format!("entity {i} has a non-finite transform"));
+                    }
+                    if !is_valid_extents(prop.extents) {
+                        problems.push(format!("entity {i} must have positive, finite extents"));
+                    }
+                    if !prop.mass.is_finite() || prop.mass <= 0.0 {
+                        problems.push(format!("entity {i} must have a positive mass"));
+                    }
+                    if !is_valid_color(prop.color) {
+                        problems.push(format!("entity {i} has a color outside of 0.0 to 1.0"));
+                    }
+                }
+                EntityDefinition::Door(door) => {
+                    if !door.translation.is_finite() || !door.rotation.is_finite() {
+                        problems.push(format!("entity {i} has a non-finite transform"));
+                    }
+                    if !is_valid_extents(door.extents) {
+                        problems.push(format!("entity {i} must have positive, finite extents"));
+                    }
+                    if !is_valid_color(door.color) {
+                        problems.push(format!("entity {i} has a color outside of 0.0 to 1.0"));
+                    }
+                    if !door.open_secs.is_finite() || door.open_secs <= 0.0 {
+                        problems.push(format!("entity {i} must take a positive time to open"));
+                    }
+
+                    let is_motion_finite = match door.motion {
+                        DoorMotion::Hinge { angle } => angle.is_finite(),
+                        DoorMotion::Slide { offset } => offset.is_finite(),
+                    };
+                    if !is_motion_finite {
+                        problems.push(format!("entity {i} has a non-finite door motion"));
+                    }
+                }
+                EntityDefinition::Pickup(pickup) => {
+                    if !pickup.translation.is_finite() || !pickup.rotation.is_finite() {
+                        problems.push(format!("entity {i} has a non-finite transform"));
+                    }
+                    if pickup.count == 0 {
+                        problems.push(format!("entity {i} must hold at least one item"));
+                    }
+                    if self.item_definition(&pickup.item).is_none() {
+                        problems.push(format!(
+                            "entity {i} refers to item `{}`, which is not defined",
+                            pickup.item
+                        ));
+                    }
+                }
+            }
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(LevelError::Invalid(problems))
+        }
+    }
+}
+
+fn is_valid_extents(extents: Vec3) -> bool {
+    extents.is_finite() && extents.cmpgt(Vec3::ZERO).all()
+}
+
+fn is_valid_color(color: Vec3) -> bool {
+    color.is_finite() && color.cmpge(Vec3::ZERO).all() && color.cmple(Vec3::ONE).all()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn level() -> LevelDefinition {
+        LevelDefinition::parse(
+            r#"(
+                spawn_points: [(translation: (0.0, 5.0, 0.0))],
+                blocks: [(translation: (0.0, -0.25, 0.0), extents: (20.0, 0.5, 20.0))],
+                lights: [(kind: Directional, rotation: (-45.0, 30.0, 0.0), intensity: 4000.0)],
+                items: [(id: "bottle", item: Misc(name: "Bottle"))],
+                entities: [
+                    Prop((translation: (0.0, 2.0, 0.0), extents: (0.5, 0.5, 0.5), mass: 5.0)),
+                    Door((
+                        translation: (3.0, 1.0, 0.0),
+                        extents: (1.0, 2.0, 0.1),
+                        motion: Hinge(angle: 90.0),
+                    )),
+                    Pickup((item: "bottle", translation: (-2.0, 1.0, 0.0))),
+                ],
+            )"#,
+        )
+        .unwrap()
+    }
+
+    fn problems(level: &LevelDefinition) -> Vec<String> {
+        match level.validate() {
+            Ok(()) => Vec::new(),
+            Err(LevelError::Invalid(problems)) => problems,
+            Err(e) => panic!("Unexpected error: {e}"),
+        }
+    }
+
+    #[test]
+    fn bundled_levels_are_valid() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../assets/levels/debug.ron");
+
+        if let Err(e) = LevelDefinition::load(&path) {
+            panic!("{}: {e}", path.display());
+        }
+    }
+
+    #[test]
+    fn requires_a_spawn_point() {
+        let mut level = level();
+        level.spawn_points.clear();
+
+        assert_eq!(problems(&level), ["there must be at least one spawn point"]);
+    }
+
+    #[test]
+    fn rejects_non_finite_values() {
+        let mut level = level();
+        level.spawn_points[0].yaw = f32::NAN;
+        level.blocks[0].translation.y = f32::INFINITY;
+        level.lights[0].intensity = f32::INFINITY;
+        if let EntityDefinition::Door(door) = &mut level.entities[1] {
+            door.motion = DoorMotion::Hinge { angle: f32::NAN };
+        }
+
+        assert_eq!(
+            problems(&level),
+            [
+                "spawn point 0 is not finite",
+                "block 0 has a non-finite transform",
+                "light 0 must have a non-negative intensity",
+                "entity 1 has a non-finite door motion",
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_unusable_sizes_masses_and_colors() {
+        let mut level = level();
+        level.blocks[0].extents.x = 0.0;
+        level.lights[0].color.z = 1.5;
+        level.items[0].mass = -1.0;
+        if let EntityDefinition::Prop(prop) = &mut level.entities[0] {
+            prop.extents.y = -0.5;
+        }
+        if let EntityDefinition::Door(door) = &mut level.entities[1] {
+            door.open_secs = 0.0;
+        }
+        if let EntityDefinition::Pickup(pickup) = &mut level.entities[2] {
+            pickup.count = 0;
+        }
+
+        assert_eq!(
+            problems(&level),
+            [
+                "block 0 must have positive, finite extents",
+                "light 0 has a color outside of 0.0 to 1.0",
+                "item `bottle` must have a positive mass",
+                "entity 0 must have positive, finite extents",
+                "entity 1 must take a positive time to open",
+                "entity 2 must hold at least one item",
+            ]
+        );
+    }
+
+    #[test]
+    fn items_must_be_defined_once() {
+        let mut level = level();
+        let duplicate = level.items[0].clone();
+        level.items.push(duplicate);
+        if let EntityDefinition::Pickup(pickup) = &mut level.entities[2] {
+            pickup.item = "key".to_owned();
+        }
+
+        assert_eq!(
+            problems(&level),
+            [
+                "item `bottle` is defined more than once",
+                "entity 2 refers to item `key`, which is not defined",
+            ]
+        );
+    }
+
+    #[test]
+    fn content_hash_ignores_formatting() {
+        let compact = LevelDefinition::parse("(spawn_points: [(translation: (0.0, 5.0, 0.0))])");
+        let spaced = LevelDefinition::parse(
+            "// A comment
+            (
+                spawn_points: [
+                    (translation: (0.0, 5.0, 0.0), yaw: 0.0),
+                ],
+            )",
+        );
+
+        assert_eq!(
+            compact.unwrap().content_hash(),
+            spaced.unwrap().content_hash()
+        );
+        assert_eq!(level().content_hash(), level().content_hash());
+    }
+
+    #[test]
+    fn content_hash_changes_with_the_level() {
+        let original = level().content_hash();
+
+        let mut moved = level();
+        moved.blocks[0].translation.x += 0.01;
+        assert_ne!(moved.content_hash(), original);
+
+        let mut locked = level();
+        if let EntityDefinition::Door(door) = &mut locked.entities[1] {
+            door.state = DoorState::Locked;
+        }
+        assert_ne!(locked.content_hash(), original);
+
+        let mut extended = level();
+        extended.spawn_points.push(SpawnPointDefinition {
+            translation: Vec3::ZERO,
+            yaw: 90.0,
+        });
+        assert_ne!(extended.content_hash(), original);
+    }
+
+    #[test]
+    fn parse_reports_invalid_levels() {
+        assert!(matches!(
+            LevelDefinition::parse("(spawn_points: [])"),
+            Err(LevelError::Invalid(_))
+        ));
+        assert!(matches!(
+            LevelDefinition::parse("(blocks: [])"),
+            Err(LevelError::Parse(_))
+        ));
+    }
+}
@@ -0,0 +1,161 @@
+use std::path::PathBuf;
+
+use avian3d::prelude::*;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::inventory::ItemStack;
+
+use self::definition::{
+    BlockDefinition, DoorState, LevelDefinition, LightDefinition, LightKind, color_from_srgb,
+    rotation_from_degrees,
+};
+
+pub mod definition;
+
+/// The level loaded when no other is chosen.
+pub const DEFAULT_LEVEL_PATH: &str = "assets/levels/debug.ron";
+
+/// Loads the level file at [`LevelPath`] on startup and spawns its static geometry.
+///
+/// Queuing a [`LevelDefinition`] as a command spawns it in place of the level already loaded, which
+/// the client does when a different level is chosen from its menu.
+///
+/// Both the server and the client add this plugin, such that their physics worlds are identical.
+/// Only the colliders are spawned here; the client adds meshes on top of every [`LevelBlock`] and
+/// lights for every [`LevelLight`]. Gameplay entities are left for the server to spawn from the
+/// [`LoadedLevel`].
+///
+/// Should the level fail to load, the problem is logged and the app exits.
+pub struct LevelPlugin;
+
+impl Plugin for LevelPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LevelPath>()
+            .add_systems(Startup, load_level);
+    }
+}
+
+/// The path of the level file loaded on startup, or of whichever level has since replaced it.
+#[derive(Resource)]
+pub struct LevelPath(pub PathBuf);
+
+impl Default for LevelPath {
+    fn default() -> Self {
+        Self(PathBuf::from(DEFAULT_LEVEL_PATH))
+    }
+}
+
+/// The level that has been loaded and spawned.
+#[derive(Resource)]
+pub struct LoadedLevel(pub LevelDefinition);
+
+/// A static cuboid of level geometry.
+#[derive(Clone, Component, Copy, Debug)]
+pub struct LevelBlock {
+    pub extents: Vec3,
+    pub color: Color,
+}
+
+/// A light placed by the level.
+#[derive(Clone, Component, Copy, Debug)]
+pub struct LevelLight {
+    pub kind: LightKind,
+    pub color: Color,
+    pub intensity: f32,
+}
+
+/// A dynamic box spawned by the server from the level's gameplay entities, and replicated to
+/// clients such that they can render it.
+#[derive(Clone, Component, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Prop {
+    pub extents: Vec3,
+    pub color: Color,
+}
+
+/// A door spawned by the server from the level's gameplay entities. Only the server knows how it
+/// moves; clients are sent its transform like any other entity.
+#[derive(Clone, Component, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Door {
+    pub extents: Vec3,
+    pub color: Color,
+    pub state: DoorState,
+}
+
+/// A stack of items lying in the world, spawned by the server from the level or when a player drops
+/// something. Players pick it up by using it.
+#[derive(Clone, Component, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Pickup {
+    pub stack: ItemStack,
+    pub extents: Vec3,
+    pub color: Color,
+}
+
+impl Command for BlockDefinition {
+    fn apply(self, world: &mut World) {
+        world.spawn((
+            LevelBlock {
+                extents: self.extents,
+                color: color_from_srgb(self.color),
+            },
+            Transform::from_translation(self.translation)
+                .with_rotation(rotation_from_degrees(self.rotation)),
+            RigidBody::Static,
+            Collider::cuboid(self.extents.x, self.extents.y, self.extents.z),
+        ));
+    }
+}
+
+impl Command for LightDefinition {
+    fn apply(self, world: &mut World) {
+        world.spawn((
+            LevelLight {
+                kind: self.kind,
+                color: color_from_srgb(self.color),
+                intensity: self.intensity,
+            },
+            Transform::from_translation(self.translation)
+                .with_rotation(rotation_from_degrees(self.rotation)),
+        ));
+    }
+}
+
+/// Spawns the level's static geometry in place of any already spawned, and makes it the
+/// [`LoadedLevel`].
+impl Command for LevelDefinition {
+    fn apply(self, world: &mut World) {
+        let spawned = world
+            .query_filtered::<Entity, Or<(With<LevelBlock>, With<LevelLight>)>>()
+            .iter(world)
+            .collect::<Vec<_>>();
+
+        for entity in spawned {
+            world.entity_mut(entity).despawn_recursive();
+        }
+
+        for block in self.blocks.iter().cloned() {
+            block.apply(world);
+        }
+
+        for light in self.lights.iter().cloned() {
+            light.apply(world);
+        }
+
+        world.insert_resource(LoadedLevel(self));
+    }
+}
+
+fn load_level(path: Res<LevelPath>, mut exit: EventWriter<AppExit>, mut commands: Commands) {
+    let level = match LevelDefinition::load(&path.0) {
+        Ok(level) => level,
+        Err(e) => {
+            error!("Error loading level `{}`: {e}", path.0.display());
+            exit.send(AppExit::error());
+            return;
+        }
+    };
+
+    info!("Loaded level `{}`.", path.0.display());
+
+    commands.queue(level);
+}
@@ -2,17 +2,38 @@ use bevy::prelude::*;
 use bevy_replicon::prelude::*;
 
 use self::{
+    announcement::S2CAnnouncement,
+    carry::{C2SThrow, Carried},
+    chat::{C2SChatMessage, S2CChatMessage},
+    disconnect::S2CDisconnectNotice,
     handshake::{C2SHandshakeStart, S2CHandshakeResult},
+    interaction::{C2SInteract, Interactable},
+    inventory::{C2SInventoryCommand, Inventory},
+    level::{Door, Pickup, Prop},
     ownership::OwnedByClient,
-    physics::components::transform::ReplicatedTransform,
+    physics::components::{
+        movement::{Crouching, MovementAcceleration},
+        transform::{ReplicatedTransform, S2CServerTick, TransformTick},
+        velocity::ReplicatedLinearVelocity,
+    },
     player::{
-        components::{Player, PlayerAvatarColor, PlayerDisplayName},
+        components::{
+            AcknowledgedInput, Disconnected, LookDirection, Player, PlayerAvatarColor,
+            PlayerDisplayName, RoundTripTime,
+        },
         messages::client_input::{C2SCommand, C2SInputEvent},
     },
 };
 
 pub mod actions;
+pub mod announcement;
+pub mod carry;
+pub mod chat;
+pub mod disconnect;
 pub mod handshake;
+pub mod interaction;
+pub mod inventory;
+pub mod level;
 pub mod ownership;
 pub mod physics;
 pub mod player;
@@ -20,18 +41,51 @@ pub mod player;
 /// A random [`u64`] value used as the protocol ID version for the versions 0.1.x of the project.
 pub const PROTOCOL_ID_V0_1: u64 = 1_542_994_232_742;
 
+/// The rate, in hertz, at which both the server and the client run their fixed time-step, unless
+/// the server is configured otherwise.
+///
+/// These must match for client-side prediction to agree with the server, so the server tells each
+/// client its rate on joining.
+pub const FIXED_TIMESTEP_HZ: f64 = 30.0;
+
+/// The version of this crate, which clients send during the handshake such that mismatched builds
+/// can be turned away.
+pub const CRATE_VERSION: &str = env!("CARGO_PKG_VERSION");
+
 pub struct ProtocolPlugin;
 
 impl Plugin for ProtocolPlugin {
     fn build(&self, app: &mut App) {
         app.replicate::<OwnedByClient>()
             .replicate::<ReplicatedTransform>()
+            .replicate::<TransformTick>()
             .replicate::<Player>()
             .replicate::<PlayerAvatarColor>()
             .replicate::<PlayerDisplayName>()
+            .replicate::<Crouching>()
+            .replicate::<ReplicatedLinearVelocity>()
+            .replicate::<AcknowledgedInput>()
+            .replicate::<LookDirection>()
+            .replicate::<MovementAcceleration>()
+            .replicate::<Disconnected>()
+            .replicate::<RoundTripTime>()
+            .replicate::<Prop>()
+            .replicate::<Door>()
+            .replicate::<Pickup>()
+            .replicate_mapped::<Carried>()
+            .replicate::<Interactable>()
+            .replicate::<Inventory>()
             .add_client_event::<C2SHandshakeStart>(ChannelKind::Ordered)
             .add_server_event::<S2CHandshakeResult>(ChannelKind::Ordered)
             .add_client_event::<C2SInputEvent>(ChannelKind::Unreliable)
-            .add_client_event::<C2SCommand>(ChannelKind::Ordered);
+            .add_client_event::<C2SCommand>(ChannelKind::Ordered)
+            .add_server_event::<S2CAnnouncement>(ChannelKind::Ordered)
+            .add_server_event::<S2CDisconnectNotice>(ChannelKind::Ordered)
+            .add_server_event::<S2CServerTick>(ChannelKind::Unreliable)
+            .add_client_event::<C2SChatMessage>(ChannelKind::Ordered)
+            .add_server_event::<S2CChatMessage>(ChannelKind::Ordered)
+            .add_mapped_client_event::<C2SInteract>(ChannelKind::Ordered)
+            .add_client_event::<C2SInventoryCommand>(ChannelKind::Ordered)
+            .add_client_event::<C2SThrow>(ChannelKind::Ordered);
     }
 }
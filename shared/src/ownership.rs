@@ -3,7 +3,7 @@ use serde::{Deserialize, Serialize};
 
 /// Marks that a given entity is "owned by" the client with the given ID.
 ///
-/// For now, it is just the player's avatar which is given this component.
+/// For now, it is just the player's avatar and their inventory which are given this component.
 #[derive(Clone, Component, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct OwnedByClient {
     pub client_id: u64,
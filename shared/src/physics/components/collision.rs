@@ -16,3 +16,114 @@ pub enum CoLayer {
     Player,
     Pickup,
 }
+
+/// The height of every player's avatar, from the bottom of its lower collider to the top of its
+/// upper one.
+pub const PLAYER_HEIGHT: f32 = 1.0;
+
+/// Build the [`ShapeCaster`] used for ground detection, along with the top and bottom colliders to
+/// be spawned as children of a player entity of the given height.
+///
+/// The server and client both build player colliders with this, such that they collide alike.
+pub fn generate_collision_components(height: f32) -> (ShapeCaster, impl Bundle, impl Bundle) {
+    let collision_sphere = Collider::sphere(height * 0.25);
+    let (ground_shape, ground_origin, ground_distance) = ground_cast(height);
+    let shape_caster = ShapeCaster::new(ground_shape, ground_origin, Quat::IDENTITY, Dir3::NEG_Y)
+        .with_ignore_self(true)
+        .with_max_distance(ground_distance)
+        .with_query_filter(SpatialQueryFilter::default().with_mask(CoLayer::Environment));
+
+    let top_collider = (
+        Transform::from_translation(Vec3::Y * (height * 0.25)),
+        collision_sphere.clone(),
+        PlayerTopCollider,
+        generate_collision_layers(),
+    );
+
+    let bottom_collider = (
+        Transform::from_translation(Vec3::NEG_Y * (height * 0.25)),
+        PlayerBottomCollider,
+        collision_sphere,
+        generate_collision_layers(),
+    );
+
+    (shape_caster, top_collider, bottom_collider)
+}
+
+/// The shape cast downwards to find the ground beneath a player of the given height, where it is
+/// cast from relative to the player's centre, and how far.
+pub fn ground_cast(height: f32) -> (Collider, Vec3, f32) {
+    (
+        Collider::sphere(height * 0.15),
+        Vec3::NEG_Y * (height * 0.4),
+        height * 0.6,
+    )
+}
+
+/// A single collider covering the solid colliders of a player of the given height, and its offset
+/// from the player's centre, for sweeping the player through the level outside of the physics step.
+///
+/// While crouching, the upper collider is a sensor, so only the lower one is covered.
+pub fn player_sweep_shape(height: f32, is_crouching: bool) -> (Collider, Vec3) {
+    let radius = height * 0.25;
+
+    if is_crouching {
+        (Collider::sphere(radius), Vec3::NEG_Y * radius)
+    } else {
+        (Collider::capsule(radius, height * 0.5), Vec3::ZERO)
+    }
+}
+
+/// How far along a ray from `origin` an object of the given bounding `radius` must be placed for it
+/// to stay clear of the solid colliders of a player of the given height, centred on `translation`.
+pub fn distance_clear_of_player(
+    height: f32,
+    translation: Vec3,
+    is_crouching: bool,
+    origin: Vec3,
+    direction: Dir3,
+    radius: f32,
+) -> f32 {
+    let sphere_radius = height * 0.25;
+    let reach = sphere_radius + radius;
+    let lower = translation + Vec3::NEG_Y * sphere_radius;
+    let upper = translation + Vec3::Y * sphere_radius;
+
+    // While crouching, the upper collider is a sensor.
+    let centres = [Some(lower), (!is_crouching).then_some(upper)];
+
+    centres.into_iter().flatten().fold(0.0, |clear, centre| {
+        // Where the ray leaves the sphere that the object can't enter around this collider, should
+        // it pass through it at all.
+        let offset = origin - centre;
+        let along = offset.dot(*direction);
+        let discriminant = along * along - (offset.length_squared() - reach * reach);
+
+        if discriminant < 0.0 {
+            clear
+        } else {
+            clear.max(-along + discriminant.sqrt())
+        }
+    })
+}
+
+/// The layers of items lying in the world, which are walked over and bumped into like anything else.
+pub fn pickup_collision_layers() -> CollisionLayers {
+    CollisionLayers::new(
+        CoLayer::Pickup,
+        [CoLayer::Player, CoLayer::Environment, CoLayer::Pickup],
+    )
+}
+
+/// The layers of an object while it is being carried. It passes through players, and is left out
+/// of ground detection, such that its carrier can neither be pushed around by it nor stand on it.
+pub fn carried_collision_layers() -> CollisionLayers {
+    CollisionLayers::new(CoLayer::Pickup, [CoLayer::Environment, CoLayer::Pickup])
+}
+
+pub fn generate_collision_layers() -> CollisionLayers {
+    CollisionLayers::new(
+        CoLayer::Player,
+        [CoLayer::Player, CoLayer::Environment, CoLayer::Pickup],
+    )
+}
@@ -1,14 +1,21 @@
 use super::collision::PlayerTopCollider;
 use avian3d::prelude::*;
 use bevy::{ecs::component::StorageType, prelude::*};
+use serde::{Deserialize, Serialize};
 
+/// Marks a player as crouching. This is replicated from the server such that every client can see
+/// which avatars are crouched.
+#[derive(Clone, Copy, Deserialize, Serialize)]
 pub struct Crouching;
 
 impl Component for Crouching {
     const STORAGE_TYPE: StorageType = StorageType::SparseSet;
     fn register_component_hooks(hooks: &mut bevy::ecs::component::ComponentHooks) {
         hooks.on_remove(|mut world, entity, _| {
-            let children = world.entity(entity).components::<&Children>();
+            // The avatar's colliders may not have been spawned yet on the client.
+            let Some(children) = world.entity(entity).get_components::<&Children>() else {
+                return;
+            };
             let mut head_entity: Option<Entity> = None;
             for child in children {
                 let is_top_collider = world.entity(*child).contains::<PlayerTopCollider>();
@@ -24,7 +31,9 @@ impl Component for Crouching {
         });
 
         hooks.on_add(|mut world, entity, _| {
-            let children = world.entity(entity).components::<&Children>();
+            let Some(children) = world.entity(entity).get_components::<&Children>() else {
+                return;
+            };
             let mut head_entity: Option<Entity> = None;
             for child in children {
                 let is_top_collider = world.entity(*child).contains::<PlayerTopCollider>();
@@ -47,15 +56,20 @@ impl Component for Grounded {
     const STORAGE_TYPE: StorageType = StorageType::SparseSet;
     fn register_component_hooks(hooks: &mut bevy::ecs::component::ComponentHooks) {
         hooks.on_add(|mut world, entity, _| {
-            if world.entity(entity).contains::<Crouching>() {
+            // Landing stands the player back up, unless there is no room above them to do so.
+            let entity_ref = world.entity(entity);
+            if entity_ref.contains::<Crouching>() && !entity_ref.contains::<HeadBlocked>() {
                 world.commands().entity(entity).remove::<Crouching>();
             }
         });
     }
 }
+/// Marks a player as having a ceiling too low above them to stand up.
 #[derive(Component)]
 pub struct HeadBlocked;
-#[derive(Component)]
+/// How quickly a player speeds up while walking. This is replicated from the server, such that
+/// clients predict their own movement with the same value.
+#[derive(Clone, Component, Copy, Debug, Deserialize, PartialEq, Serialize)]
 pub struct MovementAcceleration(pub f32);
 #[derive(Component)]
 pub struct LateralDamping(pub f32);
@@ -2,6 +2,7 @@ use bevy::prelude::*;
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Component, Copy, Deserialize, PartialEq, Serialize)]
+#[require(TransformTick)]
 pub struct ReplicatedTransform {
     pub translation: Vec3,
     pub rotation: Quat,
@@ -39,3 +40,16 @@ impl From<ReplicatedTransform> for Transform {
         }
     }
 }
+
+/// The server's fixed tick at which the accompanying [`ReplicatedTransform`] was captured.
+///
+/// Clients use this to place each received transform on a shared timeline for interpolation.
+#[derive(Clone, Component, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct TransformTick(pub u32);
+
+/// The server's latest fixed tick, sent to every client whenever it advances.
+///
+/// Transforms are only sent while they change, so clients keep their interpolation clock in step
+/// with this instead, which keeps arriving even when nothing in the world moves.
+#[derive(Clone, Copy, Debug, Deserialize, Event, Serialize)]
+pub struct S2CServerTick(pub u32);
@@ -0,0 +1,8 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// The server's view of an entity's linear velocity.
+///
+/// Clients use this to replay their own inputs on top of the authoritative state.
+#[derive(Clone, Component, Copy, Default, Deserialize, PartialEq, Serialize)]
+pub struct ReplicatedLinearVelocity(pub Vec3);
@@ -0,0 +1,133 @@
+use avian3d::prelude::*;
+use bevy::prelude::*;
+
+use super::components::{
+    collision::{CoLayer, PLAYER_HEIGHT, ground_cast},
+    movement::{Grounded, HeadBlocked, SlopeData},
+};
+use crate::player::components::Player;
+
+/// The steepest surface, in degrees, that a player can still be considered to be standing on.
+pub const MAX_GROUND_ANGLE: f32 = 50.0;
+
+/// How far above the centre of a player's avatar there must be free space for it to stand up.
+pub const HEAD_CLEARANCE: f32 = 0.6;
+
+/// Any upwards velocity above this value means the player is leaving the ground, even if the
+/// [`ShapeCaster`] still reports a hit.
+const TAKEOFF_VELOCITY: f32 = 0.5;
+
+/// Maintains [`Grounded`], [`SlopeData`] and [`HeadBlocked`] on every [`Player`] entity from its
+/// [`ShapeCaster`] and an upwards ray.
+///
+/// This is shared by the client and the server such that both sides agree on when a player may
+/// jump or stand back up.
+pub struct GroundDetectionPlugin;
+
+/// The [`SystemSet`] in which ground and ceiling checks run. Systems that act upon [`Grounded`] or
+/// [`HeadBlocked`] should be ordered after it.
+#[derive(Clone, Debug, Eq, Hash, PartialEq, SystemSet)]
+pub struct GroundDetectionSet;
+
+impl Plugin for GroundDetectionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            (update_grounded, update_head_blocked).in_set(GroundDetectionSet),
+        );
+    }
+}
+
+fn update_grounded(
+    mut query: Query<
+        (
+            Entity,
+            &ShapeHits,
+            Option<&LinearVelocity>,
+            Option<&mut SlopeData>,
+            Has<Grounded>,
+        ),
+        With<Player>,
+    >,
+    mut commands: Commands,
+) {
+    for (entity, hits, lin_vel, slope_data, is_grounded) in &mut query {
+        let vertical_speed = lin_vel.map_or(0.0, |lin_vel| lin_vel.y);
+        let ground_normal = find_ground_normal(hits.iter().map(|hit| hit.normal1), vertical_speed);
+
+        if let Some(mut slope_data) = slope_data {
+            slope_data.ground_normal = ground_normal.unwrap_or(SlopeData::default().ground_normal);
+        }
+
+        match (ground_normal.is_some(), is_grounded) {
+            (true, false) => {
+                commands.entity(entity).insert(Grounded);
+            }
+            (false, true) => {
+                commands.entity(entity).remove::<Grounded>();
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The normal of the first surface that a player could stand on, unless they are moving up off of
+/// the ground.
+fn find_ground_normal(
+    normals: impl IntoIterator<Item = Vec3>,
+    vertical_speed: f32,
+) -> Option<Vec3> {
+    if vertical_speed > TAKEOFF_VELOCITY {
+        return None;
+    }
+
+    let min_normal_y = MAX_GROUND_ANGLE.to_radians().cos();
+    normals.into_iter().find(|normal| normal.y >= min_normal_y)
+}
+
+/// Cast for the ground beneath a player at the given translation as its [`ShapeCaster`] does,
+/// returning the ground's normal should the player be standing on it.
+///
+/// This is for moving a player outside of the physics step, such as when the client replays its
+/// inputs, where the player's [`ShapeHits`] would be out of date.
+pub fn cast_for_ground(
+    spatial_query: &SpatialQuery,
+    translation: Vec3,
+    velocity: Vec3,
+) -> Option<Vec3> {
+    let (shape, origin, max_distance) = ground_cast(PLAYER_HEIGHT);
+    let filter = SpatialQueryFilter::from_mask(CoLayer::Environment);
+
+    let hit = spatial_query.cast_shape(
+        &shape,
+        translation + origin,
+        Quat::IDENTITY,
+        Dir3::NEG_Y,
+        &ShapeCastConfig::from_max_distance(max_distance),
+        &filter,
+    )?;
+
+    find_ground_normal([hit.normal1], velocity.y)
+}
+
+fn update_head_blocked(
+    spatial_query: SpatialQuery,
+    query: Query<(Entity, &Position, Has<HeadBlocked>), With<Player>>,
+    mut commands: Commands,
+) {
+    let filter = SpatialQueryFilter::from_mask(CoLayer::Environment);
+
+    for (entity, position, is_blocked) in &query {
+        let hit = spatial_query.cast_ray(position.0, Dir3::Y, HEAD_CLEARANCE, true, &filter);
+
+        match (hit.is_some(), is_blocked) {
+            (true, false) => {
+                commands.entity(entity).insert(HeadBlocked);
+            }
+            (false, true) => {
+                commands.entity(entity).remove::<HeadBlocked>();
+            }
+            _ => {}
+        }
+    }
+}
@@ -1 +1,3 @@
 pub mod components;
+#[cfg(any(feature = "client", feature = "server"))]
+pub mod ground;
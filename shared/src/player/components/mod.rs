@@ -15,3 +15,73 @@ pub struct PlayerAvatarColor(pub Color);
 /// A component denoting the display name of the connection associated with a given player entity.
 #[derive(Clone, Component, Deserialize, Eq, PartialEq, Serialize)]
 pub struct PlayerDisplayName(pub String);
+
+/// The sequence number of the most recent [`C2SInputEvent`] that the server has applied to a
+/// player.
+///
+/// The owning client uses this to tell which of its predicted inputs still need to be replayed.
+///
+/// [`C2SInputEvent`]: super::messages::client_input::C2SInputEvent
+#[derive(Clone, Component, Copy, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct AcknowledgedInput {
+    pub sequence: u32,
+}
+
+/// The lowest and highest pitch, in degrees, that a player may look.
+pub const PITCH_LIMITS: (f32, f32) = (-85.0, 90.0);
+
+/// Where a player is looking, in degrees.
+///
+/// The yaw is applied to the body of the player's avatar, while the pitch only affects the head.
+/// This is replicated such that every client can see where others are looking, and such that the
+/// server can cast rays along the same direction the player sees.
+#[derive(Clone, Component, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct LookDirection {
+    pub pitch: f32,
+    pub yaw: f32,
+}
+
+impl LookDirection {
+    /// Create a [`LookDirection`] facing horizontally along the yaw of the given rotation.
+    pub fn from_body_rotation(rotation: Quat) -> Self {
+        let (yaw, _, _) = rotation.to_euler(EulerRot::YXZ);
+
+        Self {
+            pitch: 0.0,
+            yaw: yaw.to_degrees(),
+        }
+    }
+
+    /// Turn by the given amounts, clamping the pitch to [`PITCH_LIMITS`].
+    pub fn rotate(&mut self, pitch: f32, yaw: f32) {
+        self.pitch = (self.pitch + pitch).clamp(PITCH_LIMITS.0, PITCH_LIMITS.1);
+        self.yaw = (self.yaw + yaw) % 360.0;
+    }
+
+    /// The rotation of the player's body, which only ever turns about the vertical axis.
+    pub fn body_rotation(&self) -> Quat {
+        Quat::from_axis_angle(Vec3::Y, self.yaw.to_radians())
+    }
+
+    /// The full rotation of the player's head.
+    pub fn rotation(&self) -> Quat {
+        self.body_rotation() * Quat::from_axis_angle(Vec3::X, self.pitch.to_radians())
+    }
+
+    /// The direction the player is looking in.
+    pub fn direction(&self) -> Dir3 {
+        self.rotation() * Dir3::NEG_Z
+    }
+}
+
+/// Marks the avatar of a player who has lost their connection. The avatar is held frozen in place
+/// for a grace period, during which the player may reconnect and take it back.
+#[derive(Clone, Component, Copy, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Disconnected;
+
+/// How long, in milliseconds, a message takes to reach a player's client and come back, as
+/// measured by the server. This is refreshed every so often rather than every tick.
+#[derive(Clone, Component, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct RoundTripTime {
+    pub millis: u32,
+}
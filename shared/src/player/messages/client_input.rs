@@ -1,6 +1,12 @@
+use std::fmt::{self, Display};
+
 use bevy::prelude::*;
 use serde::{Deserialize, Serialize};
 
+/// The furthest, in degrees, that a single input may turn a player about either axis. Turning
+/// further than a full circle in one tick achieves nothing that a smaller turn wouldn't.
+pub const MAX_ROTATION_PER_INPUT: f32 = 360.0;
+
 /// A small enum denoting how a digital input has been modulated since the last tick.
 ///
 /// This allows the server to assume that, for example, a crouching player remains crouching should
@@ -25,6 +31,12 @@ impl DigitalInput {
 
 #[derive(Clone, Copy, Debug, Deserialize, Event, Serialize)]
 pub struct C2SInputEvent {
+    /// A number that increases by one with every input sent by the client.
+    ///
+    /// The server echoes the sequence number of the last input it applied back to the client, such
+    /// that the client knows which of its locally predicted inputs have yet to be processed.
+    pub sequence: u32,
+
     /// Analog or digital input for movement along the "strafing" or "swaying" direction.
     ///
     /// This value may be any number from [-1.0, 1.0] such that:
@@ -68,7 +80,149 @@ pub struct C2SInputEvent {
     pub jump_button: DigitalInput,
 }
 
+impl C2SInputEvent {
+    /// Check that every analog value is a finite number within its documented range. Only a
+    /// modified client would send anything else.
+    pub fn validate(&self) -> Result<(), InvalidInput> {
+        let analog = [
+            self.translation_strafe,
+            self.translation_walk,
+            self.rotation_pitch,
+            self.rotation_yaw,
+        ];
+        if !analog.iter().all(|value| value.is_finite()) {
+            return Err(InvalidInput::NotFinite);
+        }
+
+        let translation = [self.translation_strafe, self.translation_walk];
+        if translation.iter().any(|value| value.abs() > 1.0) {
+            return Err(InvalidInput::TranslationOutOfRange);
+        }
+
+        let rotation = [self.rotation_pitch, self.rotation_yaw];
+        if rotation
+            .iter()
+            .any(|value| value.abs() > MAX_ROTATION_PER_INPUT)
+        {
+            return Err(InvalidInput::RotationOutOfRange);
+        }
+
+        Ok(())
+    }
+}
+
+/// Why a [`C2SInputEvent`] was refused.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum InvalidInput {
+    /// An analog value was NaN or infinite.
+    NotFinite,
+    TranslationOutOfRange,
+    RotationOutOfRange,
+}
+
+impl Display for InvalidInput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotFinite => write!(f, "an analog value was not a finite number"),
+            Self::TranslationOutOfRange => write!(f, "a translation was outside [-1.0, 1.0]"),
+            Self::RotationOutOfRange => write!(
+                f,
+                "a rotation was further than {MAX_ROTATION_PER_INPUT} degrees"
+            ),
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, Deserialize, Event, Serialize)]
 pub enum C2SCommand {
     ChangeAvatarColor { r: u8, g: u8, b: u8 },
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input() -> C2SInputEvent {
+        C2SInputEvent {
+            sequence: 1,
+            translation_strafe: 0.0,
+            translation_walk: 1.0,
+            rotation_pitch: -12.5,
+            rotation_yaw: 90.0,
+            crouch_button: DigitalInput::NotPressed,
+            jump_button: DigitalInput::StartPress,
+        }
+    }
+
+    #[test]
+    fn accepts_values_within_range() {
+        assert_eq!(input().validate(), Ok(()));
+
+        let at_limits = C2SInputEvent {
+            translation_strafe: -1.0,
+            translation_walk: 1.0,
+            rotation_pitch: -MAX_ROTATION_PER_INPUT,
+            rotation_yaw: MAX_ROTATION_PER_INPUT,
+            ..input()
+        };
+        assert_eq!(at_limits.validate(), Ok(()));
+    }
+
+    #[test]
+    fn rejects_values_that_are_not_finite() {
+        for value in [f32::NAN, f32::INFINITY, f32::NEG_INFINITY] {
+            let inputs = [
+                C2SInputEvent {
+                    translation_strafe: value,
+                    ..input()
+                },
+                C2SInputEvent {
+                    translation_walk: value,
+                    ..input()
+                },
+                C2SInputEvent {
+                    rotation_pitch: value,
+                    ..input()
+                },
+                C2SInputEvent {
+                    rotation_yaw: value,
+                    ..input()
+                },
+            ];
+
+            for input in inputs {
+                assert_eq!(input.validate(), Err(InvalidInput::NotFinite));
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_translation_out_of_range() {
+        let strafe = C2SInputEvent {
+            translation_strafe: 1.5,
+            ..input()
+        };
+        let walk = C2SInputEvent {
+            translation_walk: -1.01,
+            ..input()
+        };
+
+        assert_eq!(strafe.validate(), Err(InvalidInput::TranslationOutOfRange));
+        assert_eq!(walk.validate(), Err(InvalidInput::TranslationOutOfRange));
+    }
+
+    #[test]
+    fn rejects_rotation_out_of_range() {
+        let pitch = C2SInputEvent {
+            rotation_pitch: -MAX_ROTATION_PER_INPUT - 1.0,
+            ..input()
+        };
+        let yaw = C2SInputEvent {
+            rotation_yaw: 10_000.0,
+            ..input()
+        };
+
+        assert_eq!(pitch.validate(), Err(InvalidInput::RotationOutOfRange));
+        assert_eq!(yaw.validate(), Err(InvalidInput::RotationOutOfRange));
+    }
+}
@@ -2,11 +2,22 @@ use avian3d::prelude::*;
 use bevy::prelude::*;
 use bevy_replicon::prelude::Replicated;
 
-use self::components::{Player, PlayerAvatarColor, PlayerDisplayName};
-use crate::{ownership::OwnedByClient, physics::components::transform::ReplicatedTransform};
+use self::components::{
+    AcknowledgedInput, LookDirection, Player, PlayerAvatarColor, PlayerDisplayName, RoundTripTime,
+};
+use crate::{
+    ownership::OwnedByClient,
+    physics::components::{
+        collision::{PLAYER_HEIGHT, generate_collision_components, generate_collision_layers},
+        movement::{JumpImpulse, MovementAcceleration, SlopeData},
+        transform::ReplicatedTransform,
+        velocity::ReplicatedLinearVelocity,
+    },
+};
 
 pub mod components;
 pub mod messages;
+pub mod movement;
 
 #[cfg(feature = "server")]
 pub trait SpawnPlayerCommandsExt {
@@ -42,22 +53,31 @@ impl<'w, 's> SpawnPlayerCommandsExt for Commands<'w, 's> {
                 rotation,
                 scale: Vec3::ONE,
             },
+            ReplicatedLinearVelocity::default(),
+            AcknowledgedInput::default(),
+            LookDirection::from_body_rotation(rotation),
+            MovementAcceleration::default(),
+            RoundTripTime::default(),
         ));
 
-        // Then all the local physics components
+        // Then all the local physics components, which match those the client gives its own player
+        let (shape_caster, player_top, player_bottom) =
+            generate_collision_components(PLAYER_HEIGHT);
+
         cmd.insert((
             RigidBody::Dynamic,
             Transform::from_translation(translation).rotate(rotation),
-            LockedAxes::new().lock_rotation_x().lock_translation_z(),
-            ShapeCaster::new(
-                Collider::capsule(0.3, 2.0),
-                Vec3::ZERO,
-                Quat::IDENTITY,
-                Dir3::NEG_Y,
-            )
-            .with_ignore_self(true)
-            .with_max_distance(1.0),
-        ));
+            // Rotation is driven entirely by the player's look inputs.
+            LockedAxes::ROTATION_LOCKED,
+            shape_caster,
+            generate_collision_layers(),
+            JumpImpulse::default(),
+            SlopeData::default(),
+        ))
+        .with_children(|parent| {
+            parent.spawn(player_top);
+            parent.spawn(player_bottom);
+        });
 
         cmd
     }
@@ -0,0 +1,28 @@
+use bevy::prelude::*;
+
+use super::messages::client_input::{C2SInputEvent, DigitalInput};
+
+/// Whether the given input should make a player jump.
+///
+/// Jumping is only allowed on the initial press of the jump button, and only from the ground.
+pub fn wants_jump(input: &C2SInputEvent, is_grounded: bool) -> bool {
+    input.jump_button == DigitalInput::StartPress && is_grounded
+}
+
+/// Accelerate a player's `velocity` along the walking and strafing axes of the given input, relative
+/// to the `body_rotation` of the player.
+///
+/// Both the server and the client's prediction use this, so any change here changes both in lockstep.
+pub fn accelerate(
+    input: &C2SInputEvent,
+    body_rotation: Quat,
+    acceleration: f32,
+    delta_secs: f32,
+    velocity: &mut Vec3,
+) {
+    // Walking forwards moves along -Z, which is the direction the player faces.
+    let movement_direction =
+        body_rotation * Vec3::new(input.translation_strafe, 0.0, -input.translation_walk);
+
+    *velocity += movement_direction * acceleration * delta_secs;
+}